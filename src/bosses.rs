@@ -0,0 +1,122 @@
+use tcod::colors::*;
+
+use crate::structures;
+use crate::myengine;
+use crate::resources;
+use crate::config as config; // change for other game
+
+
+// spawns the bosses of the level next to the door and announces them
+pub fn enter_level(tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &mut Vec<structures::Object>) {
+    let door = match objects.iter().find(|object| object.is_door) {
+        Some(door) => door.loc(),
+        None => return,
+    };
+    let monsters_list = resources::monsters();
+    let difficulty_table = resources::difficulty();
+    let difficulty = match structures::DifficultyConfig::for_level(&difficulty_table, game.level) {
+        Some(difficulty) => difficulty,
+        None => return,
+    };
+
+    for data in monsters_list.iter() {
+        let boss_config = match &data.boss {
+            Some(boss_config) if boss_config.depth == game.level && !game.bosses.contains(&data.name) => boss_config,
+            _ => continue,
+        };
+        let (x, y) = match free_spot_near(door, &game.map, objects, &[]) {
+            Some(spot) => spot,
+            None => continue,
+        };
+        let mut boss = myengine::create_monster(x, y, data, difficulty);
+        boss.loot.extend(boss_config.guaranteed_loot.iter().map(|loot| structures::LootConfig {
+            item: loot.item,
+            chance: 1.0,
+            min_count: loot.count,
+            max_count: loot.count,
+            depth_bonus: 0.0,
+        }));
+        boss.boss = Some(structures::BossState { abilities: boss_config.abilities.clone(), ..Default::default() });
+        objects.push(boss);
+        game.bosses.push(data.name.clone());
+
+        let announcement = if boss_config.announcement.is_empty() {
+            tr!("boss.announcement", name = data.name)
+        } else {
+            boss_config.announcement.clone()
+        };
+        game.messages.add_category(structures::MessageCategory::Level, announcement.clone(), LIGHT_MAGENTA);
+        myengine::msgbox(&announcement, config::BOSS_ANNOUNCEMENT_WIDTH, &mut tcod.root);
+    }
+}
+
+// the living boss that keeps the player from going down, if any
+pub fn door_guard(objects: &[structures::Object]) -> Option<&structures::Object> {
+    objects.iter().find(|object| object.alive && object.boss.is_some())
+}
+
+// uses the abilities of the boss, once for every action it takes
+pub fn use_abilities(id: usize, game: &mut structures::Game, objects: &mut [structures::Object]) {
+    let mut state = match objects[id].boss.take() {
+        Some(state) => state,
+        None => return,
+    };
+    state.turns += 1;
+
+    for ability in &state.abilities {
+        match ability {
+            structures::BossAbility::Regenerate { amount } => objects[id].heal(*amount),
+            structures::BossAbility::Summon { monster, every_turns, max } => {
+                if state.summoned < *max && state.turns % every_turns == 0 && summon(id, monster, game, objects) {
+                    state.summoned += 1;
+                }
+            }
+            structures::BossAbility::Enrage { below_hp_fraction, damage_bonus } => {
+                let wounded = objects[id].attackable.is_some_and(|f| (f.hp as f32) < f.max_hp as f32 * below_hp_fraction);
+                if wounded && !state.enraged {
+                    state.enraged = true;
+                    if let Some(attackable) = objects[id].attackable.as_mut() {
+                        attackable.damage += damage_bonus;
+                    }
+                    game.messages.add_category(structures::MessageCategory::Combat, tr!("boss.enraged", name = objects[id].name), LIGHT_RED);
+                }
+            }
+        }
+    }
+    objects[id].boss = Some(state);
+}
+
+fn summon(id: usize, monster: &str, game: &mut structures::Game, objects: &[structures::Object]) -> bool {
+    let monsters_list = resources::monsters();
+    let difficulty_table = resources::difficulty();
+    let data = monsters_list.iter().find(|data| data.name == monster);
+    let difficulty = structures::DifficultyConfig::for_level(&difficulty_table, game.level);
+    let spot = free_spot_near(objects[id].loc(), &game.map, objects, &game.summoned);
+
+    if let (Some(data), Some(difficulty), Some((x, y))) = (data, difficulty, spot) {
+        game.messages.add_category(structures::MessageCategory::Combat, tr!("boss.summon", name = objects[id].name, monster = data.name), LIGHT_RED);
+        game.summoned.push(myengine::create_monster(x, y, data, difficulty));
+        true
+    } else {
+        false
+    }
+}
+
+// the closest free tile around the position, `pending` are objects not placed on the map yet
+fn free_spot_near((x, y): (i32, i32), map: &structures::Map, objects: &[structures::Object], pending: &[structures::Object]) -> Option<(i32, i32)> {
+    for distance in 1..=config::BOSS_SPAWN_DISTANCE {
+        for dy in -distance..=distance {
+            for dx in -distance..=distance {
+                let (spot_x, spot_y) = (x + dx, y + dy);
+                if spot_x < 0 || spot_y < 0 || spot_x >= config::MAP_WIDTH || spot_y >= config::MAP_HEIGHT {
+                    continue;
+                }
+                let taken = pending.iter().any(|object| object.loc() == (spot_x, spot_y));
+                if !taken && !myengine::is_blocked(spot_x, spot_y, map, objects) {
+                    return Some((spot_x, spot_y));
+                }
+            }
+        }
+    }
+    None
+}
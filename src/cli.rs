@@ -0,0 +1,227 @@
+use std::path::PathBuf;
+
+use crate::myengine;
+use crate::paths;
+use crate::validation;
+use crate::editor;
+use crate::map_editor;
+use crate::rogulikegame;
+
+
+pub const USAGE: &str = "\
+usage: draguation_work [command] [options]
+
+commands:
+    play                 start the game (default)
+    edit-monsters        open the monster editor
+    edit-map             open the map editor
+    validate-config      check that the config files can be loaded
+    generate-map         print a generated map as ascii
+
+options:
+    --seed <n>           seed of the random generator (play, generate-map)
+    --data-dir <dir>     directory with the font and the string tables
+    --config-dir <dir>   directory with the config files
+    --load <save>        continue the game from a save file (play)
+    --out <file>         write the map to a file instead of stdout (generate-map)
+    -h, --help           show this message";
+
+pub enum Command {
+    Play {
+        seed: Option<u64>,
+        load: Option<PathBuf>,
+    },
+    EditMonsters,
+    EditMap,
+    ValidateConfig,
+    GenerateMap {
+        seed: Option<u64>,
+        out: Option<PathBuf>,
+    },
+    Help,
+}
+
+pub struct Cli {
+    pub data_dir: Option<PathBuf>,
+    pub config_dir: Option<PathBuf>,
+    pub command: Command,
+}
+
+pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Cli, String> {
+    let mut command = None;
+    let mut seed = None;
+    let mut load = None;
+    let mut out = None;
+    let mut data_dir = None;
+    let mut config_dir = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => command = Some("help".to_string()),
+            "--seed" => {
+                let value = option_value(&arg, args.next())?;
+                seed = Some(value.parse::<u64>().map_err(|_| format!("--seed expects a number, got '{}'", value))?);
+            }
+            "--data-dir" => data_dir = Some(PathBuf::from(option_value(&arg, args.next())?)),
+            "--config-dir" => config_dir = Some(PathBuf::from(option_value(&arg, args.next())?)),
+            "--load" => load = Some(PathBuf::from(option_value(&arg, args.next())?)),
+            "--out" => out = Some(PathBuf::from(option_value(&arg, args.next())?)),
+            flag if flag.starts_with('-') => return Err(format!("unknown option '{}'", flag)),
+            name if command.is_none() => command = Some(name.to_string()),
+            extra => return Err(format!("unexpected argument '{}'", extra)),
+        }
+    }
+
+    let command = match command.as_deref().unwrap_or("play") {
+        "play" => {
+            reject(&out, "--out", "play")?;
+            Command::Play { seed, load }
+        }
+        "generate-map" => {
+            reject(&load, "--load", "generate-map")?;
+            Command::GenerateMap { seed, out }
+        }
+        name @ ("edit-monsters" | "edit-map" | "validate-config") => {
+            reject(&seed, "--seed", name)?;
+            reject(&load, "--load", name)?;
+            reject(&out, "--out", name)?;
+            match name {
+                "edit-monsters" => Command::EditMonsters,
+                "edit-map" => Command::EditMap,
+                _ => Command::ValidateConfig,
+            }
+        }
+        "help" => Command::Help,
+        name => return Err(format!("unknown command '{}'", name)),
+    };
+
+    Ok(Cli { data_dir, config_dir, command })
+}
+
+fn option_value(flag: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("{} expects a value", flag))
+}
+
+fn reject<T>(value: &Option<T>, flag: &str, command: &str) -> Result<(), String> {
+    match value {
+        Some(_) => Err(format!("{} can not be used with {}", flag, command)),
+        None => Ok(()),
+    }
+}
+
+// runs the command and returns the exit code of the process
+pub fn run(cli: Cli) -> i32 {
+    if let Command::Help = cli.command {
+        println!("{}", USAGE);
+        return 0;
+    }
+    let paths = match cli.command {
+        Command::ValidateConfig | Command::GenerateMap { .. } => paths::init_config(cli.data_dir, cli.config_dir),
+        _ => paths::init(cli.data_dir, cli.config_dir),
+    };
+    if let Err(error) = paths {
+        eprintln!("{}", error);
+        return 1;
+    }
+
+    match cli.command {
+        Command::Play { seed, load } => {
+            if let Some(seed) = seed {
+                myengine::set_seed(seed);
+            }
+            match rogulikegame::run(load) {
+                Ok(()) => 0,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    1
+                }
+            }
+        }
+        Command::EditMonsters => {
+            editor::monsters_editor();
+            0
+        }
+        Command::EditMap => match map_editor::map_editor() {
+            Ok(()) => 0,
+            Err(error) => {
+                eprintln!("{}", error);
+                1
+            }
+        },
+        Command::ValidateConfig => validate_config(),
+        Command::GenerateMap { seed, out } => generate_map(seed, out),
+        Command::Help => 0,
+    }
+}
+
+// prints every problem found in the configs, one per line, for scripts and the content pipeline
+fn validate_config() -> i32 {
+    let mut valid = true;
+    for (name, errors) in validation::validate_all() {
+        if errors.is_empty() {
+            println!("{}: ok", name);
+        }
+        for error in errors {
+            println!("{}: {}", name, error);
+            valid = false;
+        }
+    }
+    if valid { 0 } else { 1 }
+}
+
+fn generate_map(seed: Option<u64>, out: Option<PathBuf>) -> i32 {
+    if let Some(seed) = seed {
+        myengine::set_seed(seed);
+    }
+    let mut objects = vec![myengine::create_player()];
+    let map = myengine::generate_map(&mut objects, 1);
+    let text = myengine::dump_map(&map, &objects);
+
+    match out {
+        Some(path) => match std::fs::write(&path, text) {
+            Ok(()) => 0,
+            Err(error) => {
+                eprintln!("could not write {}: {}", path.display(), error);
+                1
+            }
+        },
+        None => {
+            print!("{}", text);
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Cli, String> {
+        parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn error(args: &[&str]) -> String {
+        match parse_args(args) {
+            Ok(_) => panic!("{:?} should not parse", args),
+            Err(error) => error,
+        }
+    }
+
+    #[test]
+    fn play_is_the_default_command() {
+        let cli = parse_args(&["--seed", "42"]).unwrap();
+        assert!(matches!(cli.command, Command::Play { seed: Some(42), load: None }));
+    }
+
+    #[test]
+    fn bad_arguments_are_rejected() {
+        assert_eq!(error(&["--bogus"]), "unknown option '--bogus'");
+        assert_eq!(error(&["play", "--seed"]), "--seed expects a value");
+        assert_eq!(error(&["--seed", "many"]), "--seed expects a number, got 'many'");
+        assert_eq!(error(&["play", "--out", "map.txt"]), "--out can not be used with play");
+        assert_eq!(error(&["generate-map", "--load", "save.json"]), "--load can not be used with generate-map");
+        assert_eq!(error(&["validate-config", "--seed", "1"]), "--seed can not be used with validate-config");
+        assert_eq!(error(&["fly"]), "unknown command 'fly'");
+        assert_eq!(error(&["play", "extra"]), "unexpected argument 'extra'");
+    }
+}
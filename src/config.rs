@@ -1,3 +1,4 @@
+use tcod::colors::Color;
 use tcod::map::{FovAlgorithm};
 
 // actual size of the window
@@ -15,12 +16,43 @@ pub const PANEL_Y: i32 = SCREEN_HEIGHT - PANEL_HEIGHT;
 pub const INVENTORY_WIDTH: i32 = 50;
 pub const LEVEL_SCREEN_WIDTH: i32 = 40;
 pub const CHARACTER_SCREEN_WIDTH: i32 = 30;
+pub const BINDINGS_SCREEN_WIDTH: i32 = 50;
+pub const LANGUAGE_SCREEN_WIDTH: i32 = 30;
+pub const MAIN_MENU_WIDTH: i32 = 24;
+pub const GAME_OVER_SCREEN_WIDTH: i32 = 40;
 
 // chances
 pub const HEAL_SPAWN_CHANCE: f32 = 0.25;
 pub const FIRE_SCROLL_SPAWN_CHANCE: f32 = 0.25;
 pub const DOUBLE_DAMAGE_SPAWN_CHANCE: f32 = 0.25;
 
+// inventory
+pub const MAX_CARRY_WEIGHT: i32 = 30;
+pub const HEAL_WEIGHT: i32 = 2;
+pub const FIRE_SCROLL_WEIGHT: i32 = 1;
+pub const DOUBLE_DAMAGE_WEIGHT: i32 = 2;
+pub const BLIND_WEIGHT: i32 = 3;
+pub const THROW_RANGE: i32 = 6;
+pub const THROW_DAMAGE_PER_WEIGHT: i32 = 2;
+
+// economy
+pub const GOLD_SPAWN_CHANCE: f32 = 0.3;
+pub const GOLD_MIN_AMOUNT: i32 = 5;
+pub const GOLD_MAX_AMOUNT: i32 = 20;
+pub const SHOP_SPAWN_CHANCE: f32 = 0.4;
+pub const SHOP_WIDTH: i32 = 50;
+pub const DIALOGUE_WIDTH: i32 = 50;
+
+// quests
+pub const QUEST_GIVER_SPAWN_CHANCE: f32 = 0.3;
+pub const QUEST_LOG_WIDTH: i32 = 60;
+pub const SELL_PRICE_DIVIDER: i32 = 2;
+
+// bosses
+pub const BOSS_ANNOUNCEMENT_WIDTH: i32 = 50;
+// how far from the door a boss may be placed when the tiles next to it are taken
+pub const BOSS_SPAWN_DISTANCE: i32 = 3;
+
 // skills 
 pub const HEAL_AMOUNT: i32 = 10;
 pub const FIRE_DAMAGE: i32 = 10;
@@ -28,39 +60,50 @@ pub const FIRE_RANGE: i32 = 10;
 pub const BLIND_RANGE: i32 = 3;
 pub const BLIND_NUM_TURNS: i32 = 10;
 
+// rest
+pub const REST_HEAL_AMOUNT: i32 = 1;
+pub const REST_MAX_TURNS: i32 = 200;
+
+// auto explore
+pub const AUTO_EXPLORE_PICK_UP: bool = true;
+
 // level up
 pub const LEVEL_UP_XP_BASE: i32 = 200;
 pub const LEVEL_UP_XP_PER_LEVEL: i32 = 150;
 
-pub const DAMAGE_PER_LEVEL: i32 = 2;
-pub const ARMOR_PER_LEVEL: i32 = 1;
-pub const MAX_HP_PER_LEVEL: i32 = 5;
-pub const XP_PER_LEVEL: i32 = 20;
-
 pub const PLAYER_DAMAGE_PER_LEVEL: i32 = 5;
 pub const PLAYER_ARMOR_PER_LEVEL: i32 = 3;
 pub const PLAYER_MAX_HP_PER_LEVEL: i32 = 25;
 
 pub const DEFAULT_MONSTER_XP: i32 = 75;
+pub const DEFAULT_SPAWN_WEIGHT: u32 = 10;
+
+// a monster with normal speed acts once per turn
+pub const NORMAL_SPEED: i32 = 100;
+pub const ACTION_ENERGY: i32 = 100;
+// monsters run away below this part of their HP when their AI is coward
+pub const COWARD_HP_FRACTION: f32 = 0.3;
 
 // messages
 pub const MESSAGES_X: i32 = BAR_WIDTH + 2;
 pub const MESSAGES_WIDTH: i32 = SCREEN_WIDTH - BAR_WIDTH - 2;
 pub const MESSAGES_HEIGHT: usize = PANEL_HEIGHT as usize - 1;
+pub const MAX_MESSAGES_HISTORY: usize = 500;
 
 
 // rooms
 pub const ROOM_MAX_SIZE: i32 = 10;
 pub const ROOM_MIN_SIZE: i32 = 6;
 pub const MAX_ROOMS: i32 = 19;
+// used when the difficulty config can not be loaded
 pub const MAX_ROOM_MONSTERS: i32 = 3;
 pub const MAX_ROOM_ITEMS: i32 = 2;
 
-// default colors
-// pub const COLOR_LIGHT_WALL: Color = Color {r: 106, g: 10, b: 171};
-// pub const COLOR_DARK_WALL: Color = Color {r: 42, g: 23, b: 103};
-// pub const COLOR_LIGHT_GROUND: Color = Color {r: 255, g: 207, b: 0};
-// pub const COLOR_DARK_GROUND: Color = Color {r: 77, g: 50, b: 0};
+// default colors, used when the map config can not be loaded
+pub const COLOR_LIGHT_WALL: Color = Color {r: 106, g: 10, b: 171};
+pub const COLOR_DARK_WALL: Color = Color {r: 42, g: 23, b: 103};
+pub const COLOR_LIGHT_GROUND: Color = Color {r: 255, g: 207, b: 0};
+pub const COLOR_DARK_GROUND: Color = Color {r: 77, g: 50, b: 0};
 
 // FoV
 pub const FOV_ALG: FovAlgorithm = FovAlgorithm::Basic; 
@@ -70,11 +113,47 @@ pub const FOV_RADIUS: i32 = 10;
 // config
 pub const PLAYER: usize = 0;
 
+// game name shown in the window title and on the title screen
+pub const GAME_NAME: &str = "Roguelike Game Prototype";
+
 // fps
 pub const LIMIT_FPS: i32 = 20; // 20 frames-per-second maximum
 
+// real-time mode
+pub const MONSTER_TICK_MS: u64 = 500;
+pub const HIT_ANIMATION_FRAMES: i32 = 4;
+
+// format version written to the config files, see myengine::migrate
+pub const CONFIG_VERSION: u32 = 1;
 pub const CONFIG_MONSTER_FILE_NAME: &str = "my_monster_config.json";
 pub const CONFIG_MAP_FILE_NAME: &str = "my_map_config.json";
+pub const CONFIG_DIALOGUE_FILE_NAME: &str = "my_dialogue_config.json";
+pub const CONFIG_QUEST_FILE_NAME: &str = "my_quest_config.json";
+pub const CONFIG_DIFFICULTY_FILE_NAME: &str = "my_difficulty_config.json";
+pub const CONFIG_ITEM_FILE_NAME: &str = "my_item_config.json";
+pub const CONFIG_BINDINGS_FILE_NAME: &str = "my_bindings_config.json";
+pub const SAVE_FILE_NAME: &str = "savegame.json";
+pub const FONT_FILE_NAME: &str = "arial10x10.png";
+
+// game directories, see paths.rs
+pub const APP_DIR_NAME: &str = "draguation_work";
+pub const DATA_DIR_ENV: &str = "DRAGUATION_DATA_DIR";
+pub const CONFIG_DIR_ENV: &str = "DRAGUATION_CONFIG_DIR";
+pub const USER_DIR_ENV: &str = "DRAGUATION_USER_DIR";
+
+// config files changed while playing are reloaded
+pub const HOT_RELOAD: bool = true;
+pub const HOT_RELOAD_CHECK_MS: u64 = 1000;
+
+// localisation
+pub const LANGUAGE_DIR: &str = "lang";
+pub const DEFAULT_LANGUAGE: &str = "en";
+
 pub const VERTICAL_WIDGET_SPACING: f64 = 20.0;
 pub const SMALL_VERTICAL_WIDGET_SPACING: f64 = 10.0;
-pub const TEXT_BOX_WIDTH: f64 = 200.0;
\ No newline at end of file
+pub const TEXT_BOX_WIDTH: f64 = 200.0;
+pub const MONSTER_LIST_HEIGHT: f64 = 240.0;
+pub const CHANNEL_TEXT_BOX_WIDTH: f64 = 50.0;
+pub const COLOR_SWATCH_SIZE: f64 = 30.0;
+pub const GLYPH_PREVIEW_SIZE: f64 = 60.0;
+pub const GLYPH_PREVIEW_TEXT_SIZE: f64 = 40.0;
\ No newline at end of file
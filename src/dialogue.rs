@@ -0,0 +1,49 @@
+use tcod::colors::*;
+
+use crate::structures;
+use crate::myengine;
+use crate::shop;
+use crate::quests;
+use crate::resources;
+use crate::config as config; // change for other game
+
+
+pub fn talk(npc_id: usize, tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &mut [structures::Object]) {
+    let dialogue_id = match &objects[npc_id].dialogue {
+        Some(dialogue_id) => dialogue_id.clone(),
+        None => return,
+    };
+    quests::on_talked(&dialogue_id, game);
+    let dialogues = resources::dialogues();
+    let dialogue = match dialogues.iter().find(|dialogue| dialogue.id == dialogue_id) {
+        Some(dialogue) => dialogue,
+        None => {
+            game.messages.add(tr!("dialogue.nothing_to_say", name = objects[npc_id].name), WHITE);
+            return;
+        }
+    };
+
+    let mut node_id = Some(0);
+    while let Some(node) = node_id.and_then(|id| dialogue.nodes.get(id)) {
+        let header = format!("{}: {}\n", objects[npc_id].name, node.text);
+        let options: Vec<&str> = node.options.iter().map(|option| option.text.as_str()).collect();
+        let choice = myengine::menu(&header, &options, config::DIALOGUE_WIDTH, &mut tcod.root);
+
+        let option = match choice {
+            Some(choice) => &node.options[choice],
+            None => break,
+        };
+        if let Some(action) = &option.action {
+            perform_action(action, npc_id, tcod, game, objects);
+        }
+        node_id = option.next;
+    }
+}
+
+fn perform_action(action: &structures::DialogueAction, npc_id: usize, tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &mut [structures::Object]) {
+    use structures::DialogueAction::*;
+    match action {
+        OpenShop => shop::trade(npc_id, tcod, game, objects),
+        GiveQuest(quest_id) => quests::give_quest(quest_id, game),
+    }
+}
@@ -1,12 +1,16 @@
 use crate::config;
 use crate::myengine;
-use crate::map_editor;
-use crate::rogulikegame;
+use crate::structures;
+use crate::validation;
+use crate::resources;
 
-use druid::widget::{Align, Button, Flex, TextBox};
-use druid::{AppLauncher, Data, Lens, LocalizedString, Widget, WindowDesc, WidgetExt};
+use std::sync::Arc;
 
-const WINDOW_TITLE: LocalizedString<HelloState> = LocalizedString::new("Monsters Editor");
+use druid::widget::{Align, Button, Controller, CrossAxisAlignment, Flex, Label, LineBreaking, List, Painter, RadioGroup, Scroll, Slider, TextBox};
+use druid::{theme, AppLauncher, Color, Data, Env, Event, EventCtx, Lens, LensExt, RenderContext, Selector, Widget, WindowDesc, WidgetExt};
+
+// sent by a row of the monster list when it is clicked
+const SELECT_MONSTER: Selector<usize> = Selector::new("monsters-editor.select-monster");
 
 
 #[derive(Clone, Data, Lens)]
@@ -19,15 +23,138 @@ pub struct HelloState {
     pub r: String,
     pub g: String,
     pub b: String,
+    pub xp: String,
+    pub ai: String,
+    pub speed: String,
+    pub spawn_weight: String,
+    pub min_depth: String,
+    // empty for no upper bound
+    pub max_depth: String,
+    pub min_group: String,
+    pub max_group: String,
+    pub description: String,
+    // result of the last change, shown under the buttons
+    pub status: String,
+    // the monsters of the config file, in the order of the file
+    pub monsters: Arc<Vec<MonsterRow>>,
+    // the monster shown in the form, none when a new monster is being made
+    pub selected: Option<usize>,
+    // validation errors of the config file as it is now
+    pub problems: String,
+}
+
+#[derive(Clone, Data, Lens)]
+pub struct MonsterRow {
+    pub index: usize,
+    pub label: String,
+    pub selected: bool,
+}
+
+impl HelloState {
+    // reads the monster list again, after every change of the file
+    fn refresh(&mut self) {
+        let (rows, problems) = match myengine::read_monster_list() {
+            Ok(monsters) => {
+                let rows = monsters
+                    .iter()
+                    .enumerate()
+                    .map(|(index, monster)| MonsterRow {
+                        index,
+                        label: match &monster.boss {
+                            Some(boss) => tr!("editor.boss_row", symbol = monster.symbol, name = monster.name, level = boss.depth),
+                            None => format!("{}  {}", monster.symbol, monster.name),
+                        },
+                        selected: self.selected == Some(index),
+                    })
+                    .collect();
+                let errors: Vec<String> = validation::validate_monsters(&monsters).iter().map(ToString::to_string).collect();
+                (rows, errors.join("\n"))
+            }
+            Err(error) => (vec![], error.to_string()),
+        };
+        self.monsters = Arc::new(rows);
+        self.problems = problems;
+    }
+
+    // fills the form with the monster at `index`, or empties it for a new monster
+    fn select(&mut self, index: Option<usize>) {
+        let monster = index.and_then(|index| myengine::read_monster_list().ok()?.into_iter().nth(index));
+        self.selected = monster.as_ref().and(index);
+        match monster {
+            Some(monster) => {
+                self.symbol = monster.symbol.to_string();
+                self.name = monster.name;
+                self.max_hp = monster.max_hp.to_string();
+                self.damage = monster.damage.to_string();
+                self.armor = monster.armor.to_string();
+                self.r = monster.r.to_string();
+                self.g = monster.g.to_string();
+                self.b = monster.b.to_string();
+                self.xp = monster.xp.to_string();
+                self.ai = monster.ai.name().to_string();
+                self.speed = monster.speed.to_string();
+                self.spawn_weight = monster.spawn_weight.to_string();
+                self.min_depth = monster.min_depth.to_string();
+                self.max_depth = monster.max_depth.map(|depth| depth.to_string()).unwrap_or_default();
+                self.min_group = monster.min_group.to_string();
+                self.max_group = monster.max_group.to_string();
+                self.description = monster.description;
+            }
+            None => {
+                for field in [&mut self.symbol, &mut self.name, &mut self.max_hp, &mut self.damage, &mut self.armor, &mut self.r, &mut self.g, &mut self.b, &mut self.max_depth, &mut self.description].iter_mut() {
+                    field.clear();
+                }
+                // a new monster starts with the values a config without them gets
+                self.xp = config::DEFAULT_MONSTER_XP.to_string();
+                self.ai = structures::AiKind::default().name().to_string();
+                self.speed = config::NORMAL_SPEED.to_string();
+                self.spawn_weight = config::DEFAULT_SPAWN_WEIGHT.to_string();
+                self.min_depth = "1".into();
+                self.min_group = "1".into();
+                self.max_group = "1".into();
+            }
+        }
+        self.refresh();
+    }
+
+    // shows the outcome of a change to the file, selecting the monster it points to
+    fn finish(&mut self, result: Result<Option<usize>, structures::ConfigError>, done: String) {
+        match result {
+            Ok(index) => {
+                self.select(index);
+                self.status = done;
+            }
+            Err(error) => {
+                self.refresh();
+                self.status = error.to_string();
+            }
+        }
+    }
+}
+
+// selects the monster whose row was clicked
+struct MonsterSelection;
+
+impl<W: Widget<HelloState>> Controller<HelloState, W> for MonsterSelection {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut HelloState, env: &Env) {
+        match event {
+            Event::Command(command) if command.is(SELECT_MONSTER) => {
+                data.select(command.get(SELECT_MONSTER).copied());
+                data.status.clear();
+                ctx.set_handled();
+            }
+            _ => child.event(ctx, event, data, env),
+        }
+    }
 }
 
 pub fn monsters_editor() {
       
     let main_window = WindowDesc::new(build_root_widget)
-    .title(WINDOW_TITLE)
-    .window_size((400.0, 400.0));
+    .title(tr!("editor.monsters_title"))
+    .window_size((1000.0, 720.0));
 
-    let initial_state = HelloState {
+    let mut initial_state = HelloState {
         symbol: "".into(),
         name: "".into(),
         max_hp: "".into(),
@@ -36,7 +163,21 @@ pub fn monsters_editor() {
         r: "".into(),
         g: "".into(),
         b: "".into(),
+        xp: "".into(),
+        ai: "".into(),
+        speed: "".into(),
+        spawn_weight: "".into(),
+        min_depth: "".into(),
+        max_depth: "".into(),
+        min_group: "".into(),
+        max_group: "".into(),
+        description: "".into(),
+        status: "".into(),
+        monsters: Arc::new(vec![]),
+        selected: None,
+        problems: "".into(),
     };
+    initial_state.select(None);
 
 
     AppLauncher::with_window(main_window)
@@ -47,54 +188,154 @@ pub fn monsters_editor() {
 fn build_root_widget() -> impl Widget<HelloState> {
 
     let symbol_textbox = TextBox::new()
-        .with_placeholder("What is symbol?")
+        .with_placeholder(tr!("editor.placeholder.symbol"))
         .fix_width(config::TEXT_BOX_WIDTH)
         .lens(HelloState::symbol);
 
     let name_textbox = TextBox::new()
-        .with_placeholder("What is name?")
+        .with_placeholder(tr!("editor.placeholder.name"))
         .fix_width(config::TEXT_BOX_WIDTH)
         .lens(HelloState::name);
 
     let max_hp_textbox = TextBox::new()
-        .with_placeholder("What is max HP?")
+        .with_placeholder(tr!("editor.placeholder.max_hp"))
         .fix_width(config::TEXT_BOX_WIDTH)
         .lens(HelloState::max_hp);
 
     let damage_textbox = TextBox::new()
-        .with_placeholder("What is damage?")
+        .with_placeholder(tr!("editor.placeholder.damage"))
         .fix_width(config::TEXT_BOX_WIDTH)
         .lens(HelloState::damage);
 
     let armor_textbox = TextBox::new()
-        .with_placeholder("What is armor?")
+        .with_placeholder(tr!("editor.placeholder.armor"))
         .fix_width(config::TEXT_BOX_WIDTH)
         .lens(HelloState::armor);
     
-    let r_textbox = TextBox::new()
-        .with_placeholder("What is red?")
+    let color = color_picker(&tr!("editor.color"), HelloState::r, HelloState::g, HelloState::b);
+
+    let xp_textbox = TextBox::new()
+        .with_placeholder(tr!("editor.placeholder.xp"))
+        .fix_width(config::TEXT_BOX_WIDTH)
+        .lens(HelloState::xp);
+    let speed_textbox = TextBox::new()
+        .with_placeholder(tr!("editor.placeholder.speed"))
+        .fix_width(config::TEXT_BOX_WIDTH)
+        .lens(HelloState::speed);
+    let spawn_weight_textbox = TextBox::new()
+        .with_placeholder(tr!("editor.placeholder.spawn_weight"))
+        .fix_width(config::TEXT_BOX_WIDTH)
+        .lens(HelloState::spawn_weight);
+    let min_depth_textbox = TextBox::new()
+        .with_placeholder(tr!("editor.placeholder.min_depth"))
         .fix_width(config::TEXT_BOX_WIDTH)
-        .lens(HelloState::r);
-    let g_textbox = TextBox::new()
-        .with_placeholder("What is green?")
+        .lens(HelloState::min_depth);
+    let max_depth_textbox = TextBox::new()
+        .with_placeholder(tr!("editor.placeholder.max_depth"))
         .fix_width(config::TEXT_BOX_WIDTH)
-        .lens(HelloState::g);
-    let b_textbox = TextBox::new()
-        .with_placeholder("What is blue?")
+        .lens(HelloState::max_depth);
+    let min_group_textbox = TextBox::new()
+        .with_placeholder(tr!("editor.placeholder.min_group"))
         .fix_width(config::TEXT_BOX_WIDTH)
-        .lens(HelloState::b);    
+        .lens(HelloState::min_group);
+    let max_group_textbox = TextBox::new()
+        .with_placeholder(tr!("editor.placeholder.max_group"))
+        .fix_width(config::TEXT_BOX_WIDTH)
+        .lens(HelloState::max_group);
+    let description_textbox = TextBox::multiline()
+        .with_placeholder(tr!("editor.placeholder.description"))
+        .fix_width(config::TEXT_BOX_WIDTH)
+        .lens(HelloState::description);
+    let ai_radio = RadioGroup::new(structures::AiKind::ALL.iter().map(|kind| (kind.name(), kind.name().to_string())))
+        .lens(HelloState::ai);
+
+    // the glyph as it is drawn in the game, on the lit ground of the current map palette
+    let glyph_preview = Label::dynamic(|data: &HelloState, _| data.symbol.trim().chars().next().map(String::from).unwrap_or_default())
+        .with_text_size(config::GLYPH_PREVIEW_TEXT_SIZE)
+        .env_scope(|env, data: &HelloState| env.set(theme::LABEL_COLOR, color_of(data, HelloState::r, HelloState::g, HelloState::b)))
+        .center()
+        .fix_size(config::GLYPH_PREVIEW_SIZE, config::GLYPH_PREVIEW_SIZE)
+        .background(Painter::new(|ctx, _: &HelloState, _| {
+            let map = resources::map();
+            let rect = ctx.size().to_rect();
+            ctx.fill(rect, &Color::rgb8(map.light_ground_r, map.light_ground_g, map.light_ground_b));
+        }));
+
     let _data: &HelloState;
 
 
-    let save = Button::new("Save this monster").on_click(move |_, _data: &mut HelloState, _| {
-        myengine::write_monster(_data);
+    let save = Button::new(tr!("editor.save")).on_click(move |_, _data: &mut HelloState, _| {
+        let result = myengine::write_monster(_data, _data.selected).map(Some);
+        let done = tr!("editor.saved", name = _data.name.trim());
+        _data.finish(result, done);
+        });
+
+    let new = Button::new(tr!("editor.new")).on_click(move |_, _data: &mut HelloState, _| {
+        _data.select(None);
+        _data.status.clear();
+        });
+
+    let duplicate = Button::new(tr!("editor.duplicate")).on_click(move |_, _data: &mut HelloState, _| {
+        if let Some(index) = _data.selected {
+            let result = myengine::duplicate_monster(index).map(Some);
+            let done = tr!("editor.duplicated", name = _data.name.trim());
+            _data.finish(result, done);
+        }
+        });
+
+    let delete = Button::new(tr!("editor.delete")).on_click(move |_, _data: &mut HelloState, _| {
+        if let Some(index) = _data.selected {
+            let result = myengine::delete_monster(index).map(|()| None);
+            let done = tr!("editor.deleted", name = _data.name.trim());
+            _data.finish(result, done);
+        }
+        });
+
+    let move_up = Button::new(tr!("editor.move_up")).on_click(move |_, _data: &mut HelloState, _| {
+        if let Some(index) = _data.selected {
+            _data.finish(myengine::move_monster(index, true).map(Some), "".into());
+        }
+        });
+
+    let move_down = Button::new(tr!("editor.move_down")).on_click(move |_, _data: &mut HelloState, _| {
+        if let Some(index) = _data.selected {
+            _data.finish(myengine::move_monster(index, false).map(Some), "".into());
+        }
         });
 
-    let remove = Button::new("Remove existing config").on_click(move |_, _, _| {
-            myengine::remove();
+    let remove = Button::new(tr!("editor.remove")).on_click(move |_, _data: &mut HelloState, _| {
+        _data.finish(myengine::remove().map(|()| None), tr!("editor.removed"));
         });
 
-    let layout = Flex::column()
+    let status = Label::dynamic(|data: &HelloState, _| data.status.clone())
+        .with_line_break_mode(LineBreaking::WordWrap)
+        .fix_width(config::TEXT_BOX_WIDTH);
+
+    let monster_list = Scroll::new(List::new(|| {
+        Label::dynamic(|row: &MonsterRow, _| if row.selected { format!("> {}", row.label) } else { format!("  {}", row.label) })
+            .on_click(|ctx, row: &mut MonsterRow, _| ctx.submit_command(SELECT_MONSTER.with(row.index)))
+    }))
+    .vertical()
+    .fix_size(config::TEXT_BOX_WIDTH, config::MONSTER_LIST_HEIGHT)
+    .lens(HelloState::monsters);
+
+    let problems = Label::dynamic(|data: &HelloState, _| data.problems.clone())
+        .with_line_break_mode(LineBreaking::WordWrap)
+        .fix_width(config::TEXT_BOX_WIDTH);
+
+    let list_column = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(monster_list)
+        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
+        .with_child(Flex::row().with_child(new).with_child(duplicate).with_child(delete))
+        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
+        .with_child(Flex::row().with_child(move_up).with_child(move_down))
+        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
+        .with_child(problems);
+
+    let form_column = Flex::column()
+        .with_child(glyph_preview)
+        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
         .with_child(symbol_textbox)
         .with_spacer(config::VERTICAL_WIDGET_SPACING)
         .with_child(name_textbox)
@@ -105,45 +346,97 @@ fn build_root_widget() -> impl Widget<HelloState> {
         .with_spacer(config::VERTICAL_WIDGET_SPACING)
         .with_child(armor_textbox)
         .with_spacer(config::VERTICAL_WIDGET_SPACING)
-        .with_child(r_textbox)
-        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
-        .with_child(g_textbox)
-        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
-        .with_child(b_textbox)
+        .with_child(color)
         .with_spacer(config::VERTICAL_WIDGET_SPACING)
         .with_child(save)
         .with_spacer(config::VERTICAL_WIDGET_SPACING)
-        .with_child(remove);
-
-    Align::centered(layout)
-}
+        .with_child(remove)
+        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
+        .with_child(status);
 
-// pub fn play_menu() {
-//     monsters_editor();
-//     map_editor::map_editor();
-//     rogulikegame::play();
-// }
+    let behaviour_column = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(xp_textbox)
+        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
+        .with_child(speed_textbox)
+        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
+        .with_child(Label::new(tr!("editor.ai")))
+        .with_child(ai_radio)
+        .with_spacer(config::VERTICAL_WIDGET_SPACING)
+        .with_child(spawn_weight_textbox)
+        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
+        .with_child(min_depth_textbox)
+        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
+        .with_child(max_depth_textbox)
+        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
+        .with_child(min_group_textbox)
+        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
+        .with_child(max_group_textbox)
+        .with_spacer(config::VERTICAL_WIDGET_SPACING)
+        .with_child(description_textbox);
 
-// fn build_root_menu_widget() -> impl Widget<HelloState> {
+    let layout = Flex::row()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(list_column)
+        .with_spacer(config::VERTICAL_WIDGET_SPACING)
+        .with_child(form_column)
+        .with_spacer(config::VERTICAL_WIDGET_SPACING)
+        .with_child(behaviour_column);
 
-//     let monsters_editor_button = Button::new("Monsters editor").on_click(move |_, _, _| {     
+    Align::centered(layout).controller(MonsterSelection)
+}
 
-//     });
-//     let map_editor_button = Button::new("Map Editor").on_click(move |_, _, _| {
+// a slider and a text box for each of red, green and blue, with a swatch of the resulting color
+pub fn color_picker<T: Data>(
+    title: &str,
+    r: impl Lens<T, String> + Copy + 'static,
+    g: impl Lens<T, String> + Copy + 'static,
+    b: impl Lens<T, String> + Copy + 'static,
+) -> impl Widget<T> {
+    let swatch = Painter::new(move |ctx, data: &T, _| {
+        let rect = ctx.size().to_rect();
+        ctx.fill(rect, &color_of(data, r, g, b));
+    })
+    .fix_size(config::COLOR_SWATCH_SIZE, config::COLOR_SWATCH_SIZE);
 
-//     });
-//     let play_button = Button::new("Play").on_click(move |_, _, _| {
-        
-//     });
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Flex::row().with_child(swatch).with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING).with_child(Label::new(title)))
+        .with_child(channel_slider(&tr!("editor.red"), r))
+        .with_child(channel_slider(&tr!("editor.green"), g))
+        .with_child(channel_slider(&tr!("editor.blue"), b))
+}
 
+fn channel_slider<T: Data>(channel_name: &str, lens: impl Lens<T, String> + Copy + 'static) -> impl Widget<T> {
+    let slider = Slider::new().with_range(0.0, 255.0).lens(lens.map(
+        |text: &String| channel(text),
+        // the text is only rewritten when the slider moved, so that typing in the text box is left alone
+        |text: &mut String, value: f64| {
+            if (channel(text) - value).abs() >= 0.5 {
+                *text = (value.round() as u8).to_string();
+            }
+        },
+    ));
+    let textbox = TextBox::new()
+        .with_placeholder(channel_name)
+        .fix_width(config::CHANNEL_TEXT_BOX_WIDTH)
+        .lens(lens);
 
-//     let layout = Flex::column()
-//         .with_child(monsters_editor_button)
-//         .with_spacer(config::VERTICAL_WIDGET_SPACING)
-//         .with_child(map_editor_button)
-//         .with_spacer(config::VERTICAL_WIDGET_SPACING)
-//         .with_child(play_button);
+    Flex::row()
+        .with_child(slider)
+        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
+        .with_child(textbox)
+}
 
+// a text box that does not hold a number between 0 and 255 counts as 0
+fn channel(text: &str) -> f64 {
+    text.trim().parse::<u8>().map_or(0.0, f64::from)
+}
 
-//     Align::centered(layout)
-// }
\ No newline at end of file
+fn color_of<T>(data: &T, r: impl Lens<T, String>, g: impl Lens<T, String>, b: impl Lens<T, String>) -> Color {
+    Color::rgb8(
+        r.with(data, |text| channel(text)) as u8,
+        g.with(data, |text| channel(text)) as u8,
+        b.with(data, |text| channel(text)) as u8,
+    )
+}
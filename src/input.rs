@@ -0,0 +1,275 @@
+use tcod::console::*;
+use tcod::input::{Key, KeyCode};
+
+use serde_derive::*;
+
+use crate::myengine;
+use crate::structures;
+use crate::locale;
+use crate::paths;
+use crate::resources;
+use crate::config as config; // change for other game
+
+
+// logical commands the player can issue, independent of the physical keys
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    MoveUpLeft,
+    MoveUpRight,
+    MoveDownLeft,
+    MoveDownRight,
+    Wait,
+    RestUntilHealed,
+    RestUntilDisturbed,
+    AutoExplore,
+    PickUp,
+    Drop,
+    Inventory,
+    Descend,
+    Look,
+    Fire,
+    CharacterSheet,
+    QuestLog,
+    MessageLog,
+    Rebind,
+    Exit,
+}
+
+impl Command {
+    pub const ALL: [Command; 23] = [
+        Command::MoveUp, Command::MoveDown, Command::MoveLeft, Command::MoveRight,
+        Command::MoveUpLeft, Command::MoveUpRight, Command::MoveDownLeft, Command::MoveDownRight,
+        Command::Wait, Command::RestUntilHealed, Command::RestUntilDisturbed, Command::AutoExplore,
+        Command::PickUp, Command::Drop, Command::Inventory, Command::Descend,
+        Command::Look, Command::Fire, Command::CharacterSheet, Command::QuestLog, Command::MessageLog,
+        Command::Rebind, Command::Exit,
+    ];
+
+    pub fn label(self) -> String {
+        locale::tr(&format!("command.{:?}", self))
+    }
+
+    // movement delta for the move commands
+    pub fn direction(self) -> Option<(i32, i32)> {
+        use Command::*;
+        match self {
+            MoveUp => Some((0, -1)),
+            MoveDown => Some((0, 1)),
+            MoveLeft => Some((-1, 0)),
+            MoveRight => Some((1, 0)),
+            MoveUpLeft => Some((-1, -1)),
+            MoveUpRight => Some((1, -1)),
+            MoveDownLeft => Some((-1, 1)),
+            MoveDownRight => Some((1, 1)),
+            _ => None,
+        }
+    }
+
+    // commands that can use up the player's turn
+    pub fn takes_turn(self) -> bool {
+        use Command::*;
+        self.direction().is_some() || matches!(self, Wait | Drop | Fire)
+    }
+
+    // commands that must keep their key, without them the player can not leave the game or fix the bindings
+    pub fn is_essential(self) -> bool {
+        matches!(self, Command::Rebind | Command::Exit)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Binding {
+    pub key: String,
+    pub command: Command,
+}
+
+pub type BindingsConfigJson = structures::ConfigFile<Vec<Binding>>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Preset {
+    Default,
+    ViKeys,
+    NumPad,
+}
+
+#[derive(Clone, Debug)]
+pub struct Bindings {
+    pub bindings: Vec<Binding>,
+}
+
+impl Bindings {
+    pub fn preset(preset: Preset) -> Self {
+        use Command::*;
+        let common = [
+            ("Escape", Exit),
+            ("F1", Rebind),
+            ("o", AutoExplore),
+            ("m", MessageLog),
+        ];
+        let rest = match preset {
+            Preset::ViKeys => [("z", RestUntilHealed), ("Z", RestUntilDisturbed)],
+            _ => [("r", RestUntilHealed), ("R", RestUntilDisturbed)],
+        };
+        let keys: &[(&str, Command)] = match preset {
+            Preset::Default => &[
+                ("Up", MoveUp), ("Down", MoveDown), ("Left", MoveLeft), ("Right", MoveRight),
+                ("Home", MoveUpLeft), ("PageUp", MoveUpRight), ("End", MoveDownLeft), ("PageDown", MoveDownRight),
+                ("Spacebar", Wait), ("Number1", PickUp), ("Number2", Inventory), ("Number3", Drop),
+                ("Number4", CharacterSheet), ("Number5", Descend), ("Number6", Fire), ("Number7", QuestLog),
+                ("Number8", Look),
+            ],
+            Preset::ViKeys => &[
+                ("k", MoveUp), ("j", MoveDown), ("h", MoveLeft), ("l", MoveRight),
+                ("y", MoveUpLeft), ("u", MoveUpRight), ("b", MoveDownLeft), ("n", MoveDownRight),
+                (".", Wait), ("g", PickUp), ("i", Inventory), ("d", Drop),
+                ("c", CharacterSheet), (">", Descend), ("f", Fire), ("q", QuestLog), ("x", Look),
+            ],
+            Preset::NumPad => &[
+                ("NumPad8", MoveUp), ("NumPad2", MoveDown), ("NumPad4", MoveLeft), ("NumPad6", MoveRight),
+                ("NumPad7", MoveUpLeft), ("NumPad9", MoveUpRight), ("NumPad1", MoveDownLeft), ("NumPad3", MoveDownRight),
+                ("NumPad5", Wait), ("NumPad0", PickUp), ("NumPadEnter", Inventory), ("NumPadDecimal", Drop),
+                ("NumPadDivide", CharacterSheet), ("NumPadAdd", Descend), ("NumPadMultiply", Fire), ("NumPadSubtract", QuestLog),
+                ("Delete", Look),
+            ],
+        };
+        let bindings = keys.iter().chain(rest.iter()).chain(common.iter())
+            .map(|&(key, command)| Binding { key: key.into(), command })
+            .collect();
+        Bindings { bindings }
+    }
+
+    pub fn command(&self, key: Key) -> Option<Command> {
+        let name = key_name(key)?;
+        self.bindings.iter().find(|binding| binding.key == name).map(|binding| binding.command)
+    }
+
+    pub fn keys_for(&self, command: Command) -> Vec<&str> {
+        self.bindings.iter().filter(|binding| binding.command == command).map(|binding| binding.key.as_str()).collect()
+    }
+
+    // binds the key to the command, replacing the previous keys of the command and the previous use of the key,
+    // a key of an essential command is refused and that command is returned
+    pub fn rebind(&mut self, command: Command, key: String) -> Result<(), Command> {
+        if let Some(taken) = self.bindings.iter().find(|binding| binding.key == key && binding.command != command && binding.command.is_essential()) {
+            return Err(taken.command);
+        }
+        self.bindings.retain(|binding| binding.command != command && binding.key != key);
+        self.bindings.push(Binding { key, command });
+        Ok(())
+    }
+}
+
+// printable characters are named by their text, all other keys by their key code
+pub fn key_name(key: Key) -> Option<String> {
+    match key.code {
+        KeyCode::NoKey | KeyCode::Char | KeyCode::Shift | KeyCode::Control | KeyCode::Alt => None,
+        KeyCode::Text => Some(key.text().to_string()),
+        code => Some(format!("{:?}", code)),
+    }
+}
+
+// the saved bindings, none when the player never changed them
+fn read_bindings() -> Result<Option<Bindings>, structures::ConfigError> {
+    let path = paths::user(config::CONFIG_BINDINGS_FILE_NAME);
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(structures::ConfigError::Io { path, error }),
+    };
+    let json: BindingsConfigJson = serde_json::from_str(&text)
+        .map_err(|error| structures::ConfigError::Parse { path: path.clone(), message: error.to_string() })?;
+    if json.version > config::CONFIG_VERSION {
+        return Err(structures::ConfigError::UnsupportedVersion { path, version: json.version });
+    }
+    Ok(Some(Bindings { bindings: json.saved_configs }))
+}
+
+pub fn load_bindings() -> Bindings {
+    match read_bindings() {
+        Ok(Some(bindings)) => bindings,
+        Ok(None) => Bindings::preset(Preset::Default),
+        Err(error) => {
+            resources::report_config_error(error);
+            Bindings::preset(Preset::Default)
+        }
+    }
+}
+
+pub fn save_bindings(bindings: &Bindings) -> Result<(), structures::ConfigError> {
+    // a broken file is left for the player to fix instead of being replaced by the defaults
+    read_bindings()?;
+    let path = paths::user(config::CONFIG_BINDINGS_FILE_NAME);
+    let bindings_json = BindingsConfigJson::new(bindings.bindings.clone());
+    let serialized = serde_json::to_string_pretty(&bindings_json)
+        .map_err(|error| structures::ConfigError::Parse { path: path.clone(), message: error.to_string() })?;
+    paths::create_user_dir().map_err(|error| structures::ConfigError::Io { path: path.clone(), error })?;
+    myengine::write_atomic(&path, serialized.as_bytes()).map_err(|error| structures::ConfigError::Io { path, error })
+}
+
+pub fn rebinding_screen(bindings: &mut Bindings, root: &mut Root) -> Result<(), structures::ConfigError> {
+    let choice = myengine::menu(
+        &tr!("bindings.header"),
+        &[tr!("bindings.default"), tr!("bindings.vi_keys"), tr!("bindings.numpad"), tr!("bindings.rebind")],
+        config::BINDINGS_SCREEN_WIDTH,
+        root,
+    );
+    match choice {
+        Some(0) => *bindings = Bindings::preset(Preset::Default),
+        Some(1) => *bindings = Bindings::preset(Preset::ViKeys),
+        Some(2) => *bindings = Bindings::preset(Preset::NumPad),
+        Some(3) => rebind_command(bindings, root),
+        _ => return Ok(()),
+    }
+    save_bindings(bindings)
+}
+
+fn rebind_command(bindings: &mut Bindings, root: &mut Root) {
+    let options: Vec<String> = Command::ALL.iter()
+        .map(|&command| format!("{}: {}", command.label(), bindings.keys_for(command).join(", ")))
+        .collect();
+    let choice = myengine::menu(&tr!("bindings.choose_command"), &options, config::BINDINGS_SCREEN_WIDTH, root);
+
+    if let Some(choice) = choice {
+        let command = Command::ALL[choice];
+        let header = tr!("bindings.press_key", command = command.label());
+        let options: &[&str] = &[];
+        myengine::draw_menu(&header, options, config::BINDINGS_SCREEN_WIDTH, root);
+        // letters arrive as a key press followed by a text event, so the queue must not be flushed between them
+        myengine::flush_input();
+        loop {
+            let key = root.wait_for_keypress(false);
+            if let Some(name) = key_name(key) {
+                if let Err(taken) = bindings.rebind(command, name.clone()) {
+                    myengine::msgbox(&tr!("bindings.key_taken", key = name, command = taken.label()), config::BINDINGS_SCREEN_WIDTH, root);
+                }
+                break;
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebinding_keeps_the_keys_of_essential_commands() {
+        let mut bindings = Bindings::preset(Preset::Default);
+        assert_eq!(bindings.rebind(Command::Look, "Escape".into()), Err(Command::Exit));
+        assert_eq!(bindings.rebind(Command::Wait, "F1".into()), Err(Command::Rebind));
+        assert_eq!(bindings.keys_for(Command::Exit), vec!["Escape"]);
+        assert_eq!(bindings.keys_for(Command::Look), vec!["Number8"]);
+
+        assert_eq!(bindings.rebind(Command::Look, "Spacebar".into()), Ok(()));
+        assert_eq!(bindings.keys_for(Command::Look), vec!["Spacebar"]);
+        assert!(bindings.keys_for(Command::Wait).is_empty());
+        assert_eq!(bindings.rebind(Command::Exit, "q".into()), Ok(()));
+        assert_eq!(bindings.keys_for(Command::Exit), vec!["q"]);
+    }
+}
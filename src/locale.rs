@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+use crate::paths;
+use crate::config as config; // change for other game
+
+
+// string table of the current language, keys missing in it fall back to the default language
+static STRINGS: OnceLock<RwLock<HashMap<String, String>>> = OnceLock::new();
+
+// translates a key, substituting `{name}` parameters: tr!("monster_dead", name = monster.name)
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::locale::tr($key)
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::locale::tr_with($key, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+
+fn strings() -> &'static RwLock<HashMap<String, String>> {
+    STRINGS.get_or_init(|| RwLock::new(read_table(config::DEFAULT_LANGUAGE)))
+}
+
+fn read_table(language: &str) -> HashMap<String, String> {
+    let path = paths::data(config::LANGUAGE_DIR).join(format!("{}.json", language));
+    std::fs::File::open(&path)
+        .ok()
+        .and_then(|file| serde_json::from_reader(file).ok())
+        .unwrap_or_default()
+}
+
+pub fn load(language: &str) {
+    let mut table = read_table(config::DEFAULT_LANGUAGE);
+    table.extend(read_table(language));
+    *strings().write().unwrap() = table;
+}
+
+pub fn available_languages() -> Vec<String> {
+    let mut languages: Vec<String> = std::fs::read_dir(paths::data(config::LANGUAGE_DIR))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+                .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+                .collect()
+        })
+        .unwrap_or_default();
+    languages.sort();
+    languages
+}
+
+pub fn tr(key: &str) -> String {
+    strings().read().unwrap().get(key).cloned().unwrap_or_else(|| key.to_string())
+}
+
+pub fn tr_with(key: &str, args: &[(&str, String)]) -> String {
+    let mut text = tr(key);
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
@@ -1,18 +1,30 @@
+#[macro_use]
+mod locale;
 mod myengine;
 mod rogulikegame;
 mod config;
 mod skills;
+mod shop;
+mod dialogue;
+mod quests;
+mod input;
 mod structures;
 mod editor;
 mod map_editor;
+mod cli;
+mod paths;
+mod validation;
+mod resources;
+mod bosses;
 
 
 fn main() {
-
-    //editor::monsters_editor();
-
-    //map_editor::map_editor();
-    
-    rogulikegame::play();
+    let cli = match cli::parse(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    std::process::exit(cli::run(cli));
 }
-
@@ -1,15 +1,17 @@
 use crate::structures;
 use crate::config;
 use crate::myengine;
+use crate::input;
+use crate::resources;
+use crate::editor;
 
 use tcod::console::*;
 
+use std::sync::{Arc, Mutex};
 use std::thread;
 
-use druid::widget::{Align, Button, Flex, TextBox};
-use druid::{AppLauncher, Data, Lens, LocalizedString, Widget, WindowDesc, WidgetExt};
-
-const WINDOW_TITLE: LocalizedString<HelloState> = LocalizedString::new("Map Editor");
+use druid::widget::{Align, Button, Controller, CrossAxisAlignment, Flex, Label, LineBreaking};
+use druid::{AppLauncher, Data, Env, Lens, UpdateCtx, Widget, WindowDesc, WidgetExt};
 
 
 #[derive(Clone, Data, Lens)]
@@ -26,50 +28,65 @@ pub struct HelloState {
     pub dark_ground_color_r: String,
     pub dark_ground_color_g: String,
     pub dark_ground_color_b: String,
+    // result of the last save, shown under the button
+    pub status: String,
 }
 
-pub fn map_editor() {
+pub fn map_editor() -> Result<(), String> {
+    // the preview window needs the font, without it the editor is not opened
+    let root = myengine::set_root(&tr!("editor.preview_title"))?;
       
-    let main_window = WindowDesc::new(build_root_widget)
-    .title(WINDOW_TITLE)
-    .window_size((400.0, 400.0));
+    let main_window = WindowDesc::new(move || build_root_widget(root))
+    .title(tr!("editor.map_title"))
+    .window_size((420.0, 720.0));
 
-    // create the initial app state
+    // the text boxes start with the palette in use
+    let map = resources::map();
     let initial_state = HelloState {
-        light_wall_color_r: "".into(),
-        light_wall_color_g: "".into(),
-        light_wall_color_b: "".into(),
-        dark_wall_color_r: "".into(),
-        dark_wall_color_g: "".into(),
-        dark_wall_color_b: "".into(),
-        light_ground_color_r: "".into(),
-        light_ground_color_g: "".into(),
-        light_ground_color_b: "".into(),
-        dark_ground_color_r: "".into(),
-        dark_ground_color_g: "".into(),
-        dark_ground_color_b: "".into(),
+        light_wall_color_r: map.light_wall_r.to_string(),
+        light_wall_color_g: map.light_wall_g.to_string(),
+        light_wall_color_b: map.light_wall_b.to_string(),
+        dark_wall_color_r: map.dark_wall_r.to_string(),
+        dark_wall_color_g: map.dark_wall_g.to_string(),
+        dark_wall_color_b: map.dark_wall_b.to_string(),
+        light_ground_color_r: map.light_ground_r.to_string(),
+        light_ground_color_g: map.light_ground_g.to_string(),
+        light_ground_color_b: map.light_ground_b.to_string(),
+        dark_ground_color_r: map.dark_ground_r.to_string(),
+        dark_ground_color_g: map.dark_ground_g.to_string(),
+        dark_ground_color_b: map.dark_ground_b.to_string(),
+        status: "".into(),
     };
 
     // start the application
     AppLauncher::with_window(main_window)
         .launch(initial_state)
         .expect("Failed to launch application");   
+    Ok(())
 }
 
-fn build_root_widget() -> impl Widget<HelloState> {
+fn build_root_widget(root: Root) -> impl Widget<HelloState> {
 
-    let game_name = "Editor";
-    let root = myengine::set_root(game_name);
     let mut tcod = myengine::set_tcod(root);
     
     let player = myengine::create_player();
     let mut objects = vec![player];
 
     let mut game = structures::Game {
-        map: myengine::generate_map(&mut objects),
+        map: myengine::generate_map(&mut objects, 1),
         messages: structures::Messages::new(),
         inventory: vec![],
         level: 1,
+        gold: 0,
+        dropped_items: vec![],
+        summoned: vec![],
+        bosses: vec![],
+        quests: vec![],
+        bindings: input::load_bindings(),
+        auto_action: None,
+        mode: structures::GameMode::TurnBased,
+        animations: vec![],
+        stats: Default::default(),
     };
     for y in 0..config::MAP_HEIGHT {
         for x in 0..config::MAP_WIDTH {
@@ -78,99 +95,62 @@ fn build_root_widget() -> impl Widget<HelloState> {
         }
     }
 
+    // the preview is drawn with the palette typed in the editor, saved or not
+    let palette = Arc::new(Mutex::new((*resources::map()).clone()));
+    let preview_palette = palette.clone();
     thread::spawn(move|| {
         while !tcod.root.window_closed() {
+            let palette = preview_palette.lock().unwrap().clone();
             tcod.screen.clear();
-            myengine::render(&mut tcod, &mut game, &objects, true);
+            myengine::render_with_palette(&mut tcod, &mut game, &objects, true, &palette);
             tcod.root.flush();
         }
     }); 
     
-    let light_wall_r_textbox = TextBox::new()
-        .with_placeholder("What is light wall red?")
-        .fix_width(config::TEXT_BOX_WIDTH)
-        .lens(HelloState::light_wall_color_r);
-    let light_wall_g_textbox = TextBox::new()
-        .with_placeholder("What is light wall green?")
-        .fix_width(config::TEXT_BOX_WIDTH)
-        .lens(HelloState::light_wall_color_g);
-    let light_wall_b_textbox = TextBox::new()
-        .with_placeholder("What is light wall blue?")
-        .fix_width(config::TEXT_BOX_WIDTH)
-        .lens(HelloState::light_wall_color_b);
-
-    let dark_wall_r_textbox = TextBox::new()
-        .with_placeholder("What is dark wall red?")
-        .fix_width(config::TEXT_BOX_WIDTH)
-        .lens(HelloState::dark_wall_color_r);
-    let dark_wall_g_textbox = TextBox::new()
-        .with_placeholder("What is dark wall green?")
-        .fix_width(config::TEXT_BOX_WIDTH)
-        .lens(HelloState::dark_wall_color_g);
-    let dark_wall_b_textbox = TextBox::new()
-        .with_placeholder("What is dark wall blue?")
-        .fix_width(config::TEXT_BOX_WIDTH)
-        .lens(HelloState::dark_wall_color_b);
-    
-    let light_ground_r_textbox = TextBox::new()
-        .with_placeholder("What is light ground red?")
-        .fix_width(config::TEXT_BOX_WIDTH)
-        .lens(HelloState::light_ground_color_r);
-    let light_ground_g_textbox = TextBox::new()
-        .with_placeholder("What is light ground green?")
-        .fix_width(config::TEXT_BOX_WIDTH)
-        .lens(HelloState::light_ground_color_g);
-    let light_ground_b_textbox = TextBox::new()
-        .with_placeholder("What is light ground blue?")
-        .fix_width(config::TEXT_BOX_WIDTH)
-        .lens(HelloState::light_ground_color_b);
-        
-    let dark_ground_r_textbox = TextBox::new()
-        .with_placeholder("What is dark ground red?")
-        .fix_width(config::TEXT_BOX_WIDTH)
-        .lens(HelloState::dark_ground_color_r);
-    let dark_ground_g_textbox = TextBox::new()
-        .with_placeholder("What is dark ground green?")
-        .fix_width(config::TEXT_BOX_WIDTH)
-        .lens(HelloState::dark_ground_color_g);
-    let dark_ground_b_textbox = TextBox::new()
-        .with_placeholder("What is dark ground blue?")
-        .fix_width(config::TEXT_BOX_WIDTH)
-        .lens(HelloState::dark_ground_color_b);
+    let light_wall = editor::color_picker(&tr!("editor.lit_walls"), HelloState::light_wall_color_r, HelloState::light_wall_color_g, HelloState::light_wall_color_b);
+    let dark_wall = editor::color_picker(&tr!("editor.explored_walls"), HelloState::dark_wall_color_r, HelloState::dark_wall_color_g, HelloState::dark_wall_color_b);
+    let light_ground = editor::color_picker(&tr!("editor.lit_ground"), HelloState::light_ground_color_r, HelloState::light_ground_color_g, HelloState::light_ground_color_b);
+    let dark_ground = editor::color_picker(&tr!("editor.explored_ground"), HelloState::dark_ground_color_r, HelloState::dark_ground_color_g, HelloState::dark_ground_color_b);
 
     let _data: &HelloState;
-    let generate_map_button = Button::new("generate map").on_click(move |_, _data: &mut HelloState, _| {
-        myengine::write_map(_data);
+    let generate_map_button = Button::new(tr!("editor.save_map")).on_click(move |_, _data: &mut HelloState, _| {
+        _data.status = match myengine::write_map(_data) {
+            Ok(()) => tr!("editor.map_saved"),
+            Err(error) => error.to_string(),
+        };
         });
 
-    // arrange the two widgets vertically, with some padding
+    let status = Label::dynamic(|data: &HelloState, _| data.status.clone())
+        .with_line_break_mode(LineBreaking::WordWrap)
+        .fix_width(config::TEXT_BOX_WIDTH);
+
     let layout = Flex::column()
-        .with_child(light_wall_r_textbox)
-        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
-        .with_child(light_wall_g_textbox)
-        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
-        .with_child(light_wall_b_textbox)
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(light_wall)
         .with_spacer(config::VERTICAL_WIDGET_SPACING)
-        .with_child(dark_wall_r_textbox)
-        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
-        .with_child(dark_wall_g_textbox)
-        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
-        .with_child(dark_wall_b_textbox)
+        .with_child(dark_wall)
         .with_spacer(config::VERTICAL_WIDGET_SPACING)
-        .with_child(light_ground_r_textbox)
-        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
-        .with_child(light_ground_g_textbox)
-        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
-        .with_child(light_ground_b_textbox)
+        .with_child(light_ground)
         .with_spacer(config::VERTICAL_WIDGET_SPACING)
-        .with_child(dark_ground_r_textbox)
-        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
-        .with_child(dark_ground_g_textbox)
-        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
-        .with_child(dark_ground_b_textbox)
+        .with_child(dark_ground)
         .with_spacer(config::VERTICAL_WIDGET_SPACING)
-        .with_child(generate_map_button);
+        .with_child(generate_map_button)
+        .with_spacer(config::SMALL_VERTICAL_WIDGET_SPACING)
+        .with_child(status);
     
-    // center the two widgets in the available space
-    Align::left(layout)
+    Align::left(layout).controller(PalettePreview { palette })
+}
+
+// hands every palette that can be parsed to the preview thread
+struct PalettePreview {
+    palette: Arc<Mutex<structures::MapConfig>>,
+}
+
+impl<W: Widget<HelloState>> Controller<HelloState, W> for PalettePreview {
+    fn update(&mut self, child: &mut W, ctx: &mut UpdateCtx, old_data: &HelloState, data: &HelloState, env: &Env) {
+        if let Ok(map) = myengine::map_from_form(data) {
+            *self.palette.lock().unwrap() = map;
+        }
+        child.update(ctx, old_data, data, env)
+    }
 }
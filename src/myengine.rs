@@ -1,22 +1,36 @@
 use tcod::colors::*;
 
 use std::cmp;
-use rand::Rng;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rand::distributions::{Distribution, Standard};
+use rand::distributions::uniform::{SampleRange, SampleUniform};
 use tcod::console::*;
 
 use std::io::Write;
 
 use tcod::map::{Map as FovMap};
+use tcod::input::Mouse;
+use tcod::pathfinding::AStar;
 
 use crate::skills;
+use crate::shop;
+use crate::dialogue;
+use crate::quests;
+use crate::bosses;
+use crate::locale;
+use crate::paths;
+use crate::resources;
+use crate::validation;
 use crate::structures;
 use crate::config as config; // Change for other game
 use crate::editor;
 use crate::map_editor;
 
-use std::path::Path;
 
-extern crate ajson;
 
 
 pub fn create_room(room: structures::Rect, map: &mut structures::Map) {
@@ -28,13 +42,53 @@ pub fn create_room(room: structures::Rect, map: &mut structures::Map) {
 }
 
 pub fn pick_item_up(object_id: usize, game: &mut structures::Game, objects: &mut Vec<structures::Object>) {
-    if game.inventory.len() >= 26 {
-        game.messages.add(
-            format!("Your inventory is full, cannot pick up {}.", objects[object_id].name), RED);
+    if objects[object_id].item == Some(structures::Item::Gold) {
+        let gold = objects.swap_remove(object_id);
+        game.gold += gold.count;
+        game.messages.add_category(structures::MessageCategory::Loot, tr!("loot.picked_gold", count = gold.count), GOLD);
+        return;
+    }
+    let weight = objects[object_id].item.map_or(0, |item| item.weight()) * objects[object_id].count;
+    if inventory_weight(&game.inventory) + weight > config::MAX_CARRY_WEIGHT {
+        game.messages.add_category(structures::MessageCategory::Loot,
+            tr!("loot.too_heavy", item = objects[object_id].name), RED);
     } else {
         let item = objects.swap_remove(object_id);
-        game.messages.add(format!("You picked up a {}!", item.name), GREEN);
-        game.inventory.push(item);
+        game.messages.add_category(structures::MessageCategory::Loot, tr!("loot.picked", item = item.name), GREEN);
+        if let Some(kind) = item.item {
+            quests::on_item_picked(kind, game);
+        }
+        add_to_inventory(item, game);
+    }
+}
+
+// puts the item into the inventory, merging it into a stack of identical items
+pub fn add_to_inventory(item: structures::Object, game: &mut structures::Game) {
+    let stack = game.inventory.iter_mut().find(|object| object.item == item.item);
+    match stack {
+        Some(stack) => stack.count += item.count,
+        None => game.inventory.push(item),
+    }
+}
+
+pub fn inventory_weight(inventory: &[structures::Object]) -> i32 {
+    inventory.iter().map(|object| object.item.map_or(0, |item| item.weight()) * object.count).sum()
+}
+
+// takes a single item out of the inventory stack, removing the stack when it becomes empty
+pub fn take_from_inventory(inventory_id: usize, game: &mut structures::Game) -> structures::Object {
+    if game.inventory[inventory_id].count > 1 {
+        let stack = &mut game.inventory[inventory_id];
+        stack.count -= 1;
+        let mut item = structures::Object::new(stack.x, stack.y, stack.symbol, stack.color, &stack.name, stack.blocks);
+        item.item = stack.item;
+        item.name_key = stack.name_key.clone();
+        item.always_visible = stack.always_visible;
+        item
+    } else {
+        let mut item = game.inventory.remove(inventory_id);
+        item.count = 1;
+        item
     }
 }
 
@@ -46,24 +100,60 @@ pub fn use_item(inventory_id: usize, tcod: &mut structures::Tcod, game: &mut  st
             Fire => skills::cast_fire,
             DoubleDamage => skills::cast_dd,
             Blind => skills::cast_blind,
+            Gold => {
+                game.messages.add(tr!("item.gold_unusable"), WHITE);
+                return;
+            }
         };
         match on_use(inventory_id, tcod, game, objects) {
             structures::UseResult::UsedUp => {
-                game.inventory.remove(inventory_id);
+                take_from_inventory(inventory_id, game);
             }
             structures::UseResult::Cancelled => {
-                game.messages.add("Cancelled", WHITE);
+                game.messages.add(tr!("item.cancelled"), WHITE);
             }
         }
     } else {
-        game.messages.add(format!("The {} can't be used.", game.inventory[inventory_id].name), WHITE);
+        game.messages.add(tr!("item.unusable", item = game.inventory[inventory_id].name), WHITE);
     }
 }
 
+pub fn drop_item(inventory_id: usize, game: &mut structures::Game, objects: &mut Vec<structures::Object>) {
+    let mut item = take_from_inventory(inventory_id, game);
+    let (x, y) = objects[config::PLAYER].loc();
+    item.set_loc(x, y);
+    game.messages.add_category(structures::MessageCategory::Loot, tr!("loot.dropped_by_player", item = item.name), YELLOW);
+    objects.push(item);
+}
+
+pub fn throw_item(inventory_id: usize, tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &mut Vec<structures::Object>) -> structures::UseResult {
+    let monster_id = match skills::target_monster(tcod, game, objects, config::THROW_RANGE) {
+        Some(monster_id) => monster_id,
+        None => {
+            game.messages.add(tr!("combat.throw_cancelled"), WHITE);
+            return structures::UseResult::Cancelled;
+        }
+    };
+
+    let mut item = take_from_inventory(inventory_id, game);
+    let damage = item.item.map_or(0, |item| item.weight()) * config::THROW_DAMAGE_PER_WEIGHT;
+    let (x, y) = objects[monster_id].loc();
+    game.messages.add_category(structures::MessageCategory::Combat, tr!("combat.throw", item = item.name, target = objects[monster_id].name, damage = damage), LIGHT_ORANGE);
+    if let Some(xp) = objects[monster_id].get_damage(damage, game) {
+        objects[config::PLAYER].attackable.as_mut().unwrap().xp += xp;
+    }
+    item.set_loc(x, y);
+    objects.push(item);
+    structures::UseResult::UsedUp
+}
+
 
 pub fn render(tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &[structures::Object], fov_recompute: bool) {
+    render_with_palette(tcod, game, objects, fov_recompute, &resources::map());
+}
 
-    let data = get_map_config();
+// renders with the given map colors instead of the configured ones, for the preview of the map editor
+pub fn render_with_palette(tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &[structures::Object], fov_recompute: bool, data: &structures::MapConfig) {
     let dark_wall_color = Color {r: data.dark_wall_r, g: data.dark_wall_g, b: data.dark_wall_b};
     let light_wall_color = Color {r: data.light_wall_r, g: data.light_wall_g, b: data.light_wall_b};
     let dark_ground_color = Color {r: data.dark_ground_r, g: data.dark_ground_g, b: data.dark_ground_b};
@@ -104,38 +194,68 @@ pub fn render(tcod: &mut structures::Tcod, game: &mut structures::Game, objects:
         object.draw(&mut tcod.screen);
     }
 
+    for animation in &game.animations {
+        tcod.screen.set_default_foreground(animation.color);
+        tcod.screen.put_char(animation.x, animation.y, animation.symbol, BackgroundFlag::None);
+    }
+    for animation in game.animations.iter_mut() {
+        animation.frames -= 1;
+    }
+    game.animations.retain(|animation| animation.frames > 0);
+
     blit(&tcod.screen, (0, 0), (config::MAP_WIDTH, config::MAP_HEIGHT), &mut tcod.root, (0, 0), 1.0, 1.0);
 
     
     tcod.root.set_default_foreground(WHITE);
     if let Some(attackable) = objects[config::PLAYER].attackable {
-        tcod.root.print_ex(1, config::SCREEN_HEIGHT - 2, BackgroundFlag::None, TextAlignment::Left, format!("HP: {}/{} ", attackable.hp, attackable.max_hp));
+        tcod.root.print_ex(1, config::SCREEN_HEIGHT - 2, BackgroundFlag::None, TextAlignment::Left, tr!("panel.hp_short", hp = attackable.hp, max_hp = attackable.max_hp));
     }
     
     tcod.panel.set_default_background(BLACK);
     tcod.panel.clear();
 
     let mut y = config::MESSAGES_HEIGHT as i32;
-    for &(ref msg, color) in game.messages.iter().rev() {
-        let msg_height = tcod.panel.get_height_rect(config::MESSAGES_X, y, config::MESSAGES_WIDTH, 0, msg);
+    for message in game.messages.iter().rev() {
+        let msg = message.display();
+        let msg_height = tcod.panel.get_height_rect(config::MESSAGES_X, y, config::MESSAGES_WIDTH, 0, &msg);
         y -= msg_height;
         if y < 0 {
             break;
         }
-        tcod.panel.set_default_foreground(color);
+        tcod.panel.set_default_foreground(message.color);
         tcod.panel.print_rect(config::MESSAGES_X, y, config::MESSAGES_WIDTH, 0, msg);
     }
 
+    tcod.panel.set_default_foreground(LIGHT_GREY);
+    tcod.panel.print_ex(1, 0, BackgroundFlag::None, TextAlignment::Left, get_names_under_mouse(tcod.mouse, objects, &tcod.fov));
+
     let hp = objects[config::PLAYER].attackable.map_or(0, |f| f.hp);
     let max_hp = objects[config::PLAYER].attackable.map_or(0, |f| f.max_hp);
-    render_bar(&mut tcod.panel, 1, 1, config::BAR_WIDTH, "HP", hp, max_hp, LIGHT_RED, DARKER_RED);
+    render_bar(&mut tcod.panel, 1, 1, config::BAR_WIDTH, &tr!("panel.hp"), hp, max_hp, LIGHT_RED, DARKER_RED);
 
-    tcod.panel.print_ex(1, 3, BackgroundFlag::None, TextAlignment::Left, format!("Dungeon level: {}", game.level));
+    tcod.panel.print_ex(1, 3, BackgroundFlag::None, TextAlignment::Left, tr!("panel.dungeon_level", level = game.level));
+    tcod.panel.set_default_foreground(GOLD);
+    tcod.panel.print_ex(1, 4, BackgroundFlag::None, TextAlignment::Left, tr!("panel.gold", gold = game.gold));
 
     blit(&tcod.panel, (0, 0), (config::SCREEN_WIDTH, config::PANEL_HEIGHT), &mut tcod.root, (0, config::PANEL_Y), 1.0, 1.0);
 
 }
 
+fn get_names_under_mouse(mouse: Mouse, objects: &[structures::Object], fov_map: &FovMap) -> String {
+    let (x, y) = (mouse.cx as i32, mouse.cy as i32);
+
+    let names = objects
+        .iter()
+        .filter(|object| object.loc() == (x, y) && fov_map.is_in_fov(object.x, object.y))
+        .map(|object| match object.attackable {
+            Some(attackable) => tr!("panel.object_hp", name = object.name, hp = attackable.hp, max_hp = attackable.max_hp),
+            None => object.name.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    names.join(", ")
+}
+
 pub fn render_bar(panel: &mut Offscreen, x: i32, y: i32, total_width: i32, name: &str, value: i32, maximum: i32, bar_color: Color, back_color: Color) {
     let bar_width = (value as f32 / maximum as f32 * total_width as f32) as i32;
 
@@ -151,7 +271,24 @@ pub fn render_bar(panel: &mut Offscreen, x: i32, y: i32, total_width: i32, name:
     panel.print_ex(x + total_width / 2, y, BackgroundFlag::None, TextAlignment::Center, &format!("{}: {}/{}", name, value, maximum));
 }
 
-pub fn generate_map(objects: &mut Vec<structures::Object>) -> structures::Map {
+thread_local! {
+    // every random roll of the game goes through this generator, so a seed reproduces the dungeon
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+pub fn set_seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+pub fn gen_range<T: SampleUniform, R: SampleRange<T>>(range: R) -> T {
+    RNG.with(|rng| rng.borrow_mut().gen_range(range))
+}
+
+pub fn random<T>() -> T where Standard: Distribution<T> {
+    RNG.with(|rng| rng.borrow_mut().gen())
+}
+
+pub fn generate_map(objects: &mut Vec<structures::Object>, level: u32) -> structures::Map {
 
     let mut map = vec![vec![structures::Tile::wall(); config::MAP_HEIGHT as usize]; config::MAP_WIDTH as usize];
 
@@ -162,11 +299,11 @@ pub fn generate_map(objects: &mut Vec<structures::Object>) -> structures::Map {
 
     for _ in 0..config::MAX_ROOMS {
 
-        let width = rand::thread_rng().gen_range(config::ROOM_MIN_SIZE..config::ROOM_MAX_SIZE + 1);
-        let height = rand::thread_rng().gen_range(config::ROOM_MIN_SIZE..config::ROOM_MAX_SIZE + 1);
+        let width = gen_range(config::ROOM_MIN_SIZE..config::ROOM_MAX_SIZE + 1);
+        let height = gen_range(config::ROOM_MIN_SIZE..config::ROOM_MAX_SIZE + 1);
 
-        let x = rand::thread_rng().gen_range(0..config::MAP_WIDTH - width);
-        let y = rand::thread_rng().gen_range(0..config::MAP_HEIGHT - height);
+        let x = gen_range(0..config::MAP_WIDTH - width);
+        let y = gen_range(0..config::MAP_HEIGHT - height);
 
         let new_room = structures::Rect::new(x, y, width, height);
 
@@ -176,7 +313,7 @@ pub fn generate_map(objects: &mut Vec<structures::Object>) -> structures::Map {
 
             create_room(new_room, &mut map);
 
-            spawn_objects(new_room, &map, objects);
+            spawn_objects(new_room, &map, objects, level);
 
             let (new_x, new_y) = new_room.center();
             // println!("new {}, {}", new_x, new_y);
@@ -188,7 +325,7 @@ pub fn generate_map(objects: &mut Vec<structures::Object>) -> structures::Map {
                 let (prev_x, prev_y) = rooms[rooms.len() - 1].center();
                 // println!("prev {}, {}", prev_x, prev_y);
 
-                if rand::random() {
+                if random() {
                     create_hor_tunnel(prev_x, new_x, prev_y, &mut map);
                     create_ver_tunnel(new_x, prev_y, new_y,  &mut map);
                 } else {
@@ -200,90 +337,282 @@ pub fn generate_map(objects: &mut Vec<structures::Object>) -> structures::Map {
         }
     }
 
+    if rooms.len() > 2 && random::<f32>() < config::SHOP_SPAWN_CHANCE {
+        let room_id = gen_range(1..rooms.len() - 1);
+        shop::spawn_shopkeeper(rooms[room_id], &map, objects);
+    }
+
+    if rooms.len() > 2 && random::<f32>() < config::QUEST_GIVER_SPAWN_CHANCE {
+        let room_id = gen_range(1..rooms.len() - 1);
+        quests::spawn_quest_giver(rooms[room_id], &map, objects);
+    }
+
     let (last_room_x, last_room_y) = rooms[rooms.len() - 1].center();
-    let mut door = structures::Object::new(last_room_x, last_room_y, '<', WHITE, "door", false);
+    let mut door = structures::Object::translated(last_room_x, last_room_y, '<', WHITE, structures::NameKey::new("object.door"), false);
     door.always_visible = true;
+    door.is_door = true;
     objects.push(door);
 
     map
 }
 
-fn get_monster_config() ->  Vec<structures::MonsterConfig> {
-    let my_existing_file = std::fs::File::open(config::CONFIG_MONSTER_FILE_NAME).unwrap();
-    let json = ajson::parse_from_read(my_existing_file).unwrap();   
-    let monsters_list = json.get("saved_configs").unwrap();
-    let deserialized: Vec<structures::MonsterConfig> = serde_json::from_str(&monsters_list.as_str()).unwrap();
-    deserialized
+// a config is looked up with every supported extension, `file_name` gives the default one
+pub fn config_file_path(file_name: &str) -> PathBuf {
+    let default_path = paths::config(file_name);
+    structures::ConfigFormat::ALL
+        .iter()
+        .map(|format| default_path.with_extension(format.extension()))
+        .find(|path| path.is_file())
+        .unwrap_or(default_path)
+}
+
+fn read_config_file<T: serde::de::DeserializeOwned>(file_name: &str) -> Result<structures::ConfigFile<T>, structures::ConfigError> {
+    read_config_path(config_file_path(file_name))
 }
 
-fn get_map_config() ->  structures::MapConfig {
-    let my_existing_file = std::fs::File::open(config::CONFIG_MAP_FILE_NAME).unwrap();
-    let json = ajson::parse_from_read(my_existing_file).unwrap();   
-    let map_colors = json.get("saved_configs").unwrap();
-    let deserialized: structures::MapConfig = serde_json::from_str(&map_colors.as_str()).unwrap();
-    deserialized
+fn read_config_path<T: serde::de::DeserializeOwned>(path: PathBuf) -> Result<structures::ConfigFile<T>, structures::ConfigError> {
+    let text = std::fs::read_to_string(&path).map_err(|error| structures::ConfigError::Io { path: path.clone(), error })?;
+    let parsed = match structures::ConfigFormat::from_path(&path) {
+        structures::ConfigFormat::Json => serde_json::from_str(&text).map_err(|error| error.to_string()),
+        structures::ConfigFormat::Toml => toml::from_str(&text).map_err(|error| error.to_string()),
+        structures::ConfigFormat::Ron => ron::from_str(&text).map_err(|error| error.to_string()),
+    };
+    let json = parsed.map_err(|message| structures::ConfigError::Parse { path: path.clone(), message })?;
+    migrate(json, path)
 }
 
-fn spawn_objects(room: structures::Rect, map: &structures::Map, objects: &mut Vec<structures::Object>) {
+// upgrades configs written by older versions of the game, so far every version has the layout of version 0
+fn migrate<T>(json: structures::ConfigFile<T>, path: PathBuf) -> Result<structures::ConfigFile<T>, structures::ConfigError> {
+    match json.version {
+        version if version > config::CONFIG_VERSION => Err(structures::ConfigError::UnsupportedVersion { path, version }),
+        _ => Ok(json),
+    }
+}
+
+fn check_config<T>(file_name: &str, config: T, errors: Vec<structures::ValidationError>) -> Result<T, structures::ConfigError> {
+    if errors.is_empty() {
+        Ok(config)
+    } else {
+        Err(structures::ConfigError::Invalid { path: config_file_path(file_name), errors })
+    }
+}
+
+pub fn read_monster_config() -> Result<Vec<structures::MonsterConfig>, structures::ConfigError> {
+    let (monsters, errors) = read_monsters()?;
+    check_config(config::CONFIG_MONSTER_FILE_NAME, monsters, errors)
+}
+
+// the monsters of the config without the invalid ones, which are reported, so one broken entry
+// does not replace the whole file with the defaults
+pub fn read_valid_monsters() -> Result<Vec<structures::MonsterConfig>, structures::ConfigError> {
+    let (monsters, errors) = read_monsters()?;
+    if errors.is_empty() {
+        return Ok(monsters);
+    }
+    let path = config_file_path(config::CONFIG_MONSTER_FILE_NAME);
+    let monsters: Vec<structures::MonsterConfig> = monsters
+        .into_iter()
+        .enumerate()
+        .filter(|(index, monster)| validation::is_valid_monster(*index, monster, &errors))
+        .map(|(_, monster)| monster)
+        .collect();
+    resources::report_config_error(structures::ConfigError::Invalid { path: path.clone(), errors });
+    if monsters.is_empty() {
+        return Err(structures::ConfigError::NoMonsters(path));
+    }
+    Ok(monsters)
+}
+
+fn read_monsters() -> Result<(Vec<structures::MonsterConfig>, Vec<structures::ValidationError>), structures::ConfigError> {
+    let json: structures::MonsterConfigJson = read_config_file(config::CONFIG_MONSTER_FILE_NAME)?;
+    let monsters = json.saved_configs;
+    if monsters.is_empty() {
+        return Err(structures::ConfigError::NoMonsters(config_file_path(config::CONFIG_MONSTER_FILE_NAME)));
+    }
+    let errors = validation::validate_monsters(&monsters);
+    Ok((monsters, errors))
+}
+
+pub fn read_dialogue_config() -> Result<Vec<structures::DialogueConfig>, structures::ConfigError> {
+    let json: structures::DialogueConfigJson = read_config_file(config::CONFIG_DIALOGUE_FILE_NAME)?;
+    let dialogues = json.saved_configs;
+    let errors = validation::validate_dialogues(&dialogues);
+    check_config(config::CONFIG_DIALOGUE_FILE_NAME, dialogues, errors)
+}
+
+pub fn read_quest_config() -> Result<Vec<structures::QuestConfig>, structures::ConfigError> {
+    let json: structures::QuestConfigJson = read_config_file(config::CONFIG_QUEST_FILE_NAME)?;
+    let quests = json.saved_configs;
+    let errors = validation::validate_quests(&quests);
+    check_config(config::CONFIG_QUEST_FILE_NAME, quests, errors)
+}
+
+pub fn read_map_config() -> Result<structures::MapConfig, structures::ConfigError> {
+    let json: structures::MapConfigJson = read_config_file(config::CONFIG_MAP_FILE_NAME)?;
+    let map_config = json.saved_configs;
+    let errors = validation::validate_map(&map_config);
+    check_config(config::CONFIG_MAP_FILE_NAME, map_config, errors)
+}
+
+pub fn read_difficulty_config() -> Result<Vec<structures::DifficultyConfig>, structures::ConfigError> {
+    let json: structures::DifficultyConfigJson = read_config_file(config::CONFIG_DIFFICULTY_FILE_NAME)?;
+    let difficulty = json.saved_configs;
+    let errors = validation::validate_difficulty(&difficulty);
+    check_config(config::CONFIG_DIFFICULTY_FILE_NAME, difficulty, errors)
+}
+
+pub fn read_item_config() -> Result<Vec<structures::ItemConfig>, structures::ConfigError> {
+    let json: structures::ItemConfigJson = read_config_file(config::CONFIG_ITEM_FILE_NAME)?;
+    let items = json.saved_configs;
+    let errors = validation::validate_items(&items);
+    check_config(config::CONFIG_ITEM_FILE_NAME, items, errors)
+}
+
+fn spawn_objects(room: structures::Rect, map: &structures::Map, objects: &mut Vec<structures::Object>, level: u32) {
     
-    let monster_num = rand::thread_rng().gen_range(0..config::MAX_ROOM_MONSTERS + 1);
-    let monsters_list = get_monster_config();
+    let difficulty_table = resources::difficulty();
+    let difficulty = match structures::DifficultyConfig::for_level(&difficulty_table, level) {
+        Some(difficulty) => difficulty,
+        None => return,
+    };
+    let monster_num = gen_range(0..difficulty.max_room_monsters + 1);
+    let monsters_list = resources::monsters();
+    let allowed: Vec<(&structures::MonsterConfig, u32)> = monsters_list
+        .iter()
+        .filter(|data| data.boss.is_none() && data.allowed_at(level))
+        .map(|data| (data, difficulty.spawn_weight(data)))
+        .collect();
 
     for _ in 0..monster_num {
-        let x = rand::thread_rng().gen_range(room.x1..room.x2+1);
-        let y = rand::thread_rng().gen_range(room.y1..room.y2+1);
-        if !is_blocked(x, y, map, objects) {
-            let mut monster;
-            let num = rand::thread_rng().gen_range(0..monsters_list.len());
-            let data = &monsters_list[num];
-            let color = Color {r: data.r, g: data.g, b: data.b};
-            monster = structures::Object::new(x, y, data.symbol, color, &data.name, true);
-            monster.attackable = Some(structures::Attackable{max_hp: data.max_hp, hp: data.max_hp, armor: data.armor, damage: data.damage, xp: config::DEFAULT_MONSTER_XP, on_death: structures::DeathCallback::Monster});
-            monster.alive = true;
-            monster.ai = Some(structures::Ai::Basic);
-            //monster.always_visible = true;
-            objects.push(monster);
+        let data = match choose_monster(&allowed) {
+            Some(data) => data,
+            None => break,
+        };
+        // every member of the group gets its own spot, a member without a free spot is left out
+        let group_size = gen_range(data.min_group..data.max_group + 1);
+        for _ in 0..group_size {
+            let x = gen_range(room.x1..room.x2+1);
+            let y = gen_range(room.y1..room.y2+1);
+            if !is_blocked(x, y, map, objects) {
+                objects.push(create_monster(x, y, data, difficulty));
+            }
         }
     }
 
-    let num_items = rand::thread_rng().gen_range(0..config::MAX_ROOM_ITEMS + 1);
+    let num_items = gen_range(0..difficulty.max_room_items + 1);
 
     for _ in 0..num_items {
-        let x = rand::thread_rng().gen_range(room.x1 + 1..room.x2);
-        let y = rand::thread_rng().gen_range(room.y1 + 1..room.y2);
+        let x = gen_range(room.x1 + 1..room.x2);
+        let y = gen_range(room.y1 + 1..room.y2);
 
         if !is_blocked(x, y, map, objects) {
-            let chance = rand::random::<f32>();
+            let chance = random::<f32>();
             let item = if chance < config::HEAL_SPAWN_CHANCE {
-                let mut object = structures::Object::new(x, y, '!',  YELLOW, "healing potion", false);
-                object.item = Some(structures::Item::Heal);
-                object.always_visible = true;
-                object
+                structures::Item::Heal
             } else if chance < config::HEAL_SPAWN_CHANCE + config::FIRE_SCROLL_SPAWN_CHANCE {
-                let mut object = structures::Object::new(x, y, '#', RED, "scroll of fire mark", false);
-                object.item = Some(structures::Item::Fire);
-                object.always_visible = true;
-                object
-            }
-            else if chance < config::HEAL_SPAWN_CHANCE + config::FIRE_SCROLL_SPAWN_CHANCE + config::DOUBLE_DAMAGE_SPAWN_CHANCE {
-                let mut object = structures::Object::new(x, y, '$', LIGHT_BLUE, "double damage", false);
-                object.item = Some(structures::Item::DoubleDamage);
-                object.always_visible = true;
-                object
+                structures::Item::Fire
+            } else if chance < config::HEAL_SPAWN_CHANCE + config::FIRE_SCROLL_SPAWN_CHANCE + config::DOUBLE_DAMAGE_SPAWN_CHANCE {
+                structures::Item::DoubleDamage
             } else {
-                let mut object = structures::Object::new(x, y, '?', GREY, "Flesh", false);
-                object.item = Some(structures::Item::Blind);
-                object.always_visible = true;
-                object
-            }
-            ;
-            objects.push(item);
+                structures::Item::Blind
+            };
+            objects.push(structures::Object::new_item(x, y, item));
+        }
+    }
+
+    if random::<f32>() < config::GOLD_SPAWN_CHANCE {
+        let x = gen_range(room.x1 + 1..room.x2);
+        let y = gen_range(room.y1 + 1..room.y2);
+        if !is_blocked(x, y, map, objects) {
+            let mut gold = structures::Object::new_item(x, y, structures::Item::Gold);
+            gold.count = gen_range(config::GOLD_MIN_AMOUNT..config::GOLD_MAX_AMOUNT + 1);
+            objects.push(gold);
         }
     }
 
 }
 
-fn is_blocked(x: i32, y: i32, map: &structures::Map, objects: &[structures::Object]) -> bool {
+// ascii picture of the map: walls are '#', floor is '.' and objects are drawn with their symbols
+pub fn dump_map(map: &structures::Map, objects: &[structures::Object]) -> String {
+    let mut rows: Vec<Vec<char>> = (0..config::MAP_HEIGHT as usize)
+        .map(|y| (0..config::MAP_WIDTH as usize).map(|x| if map[x][y].collision_enabled { '#' } else { '.' }).collect())
+        .collect();
+    // the player is drawn last so that it is never hidden
+    for object in objects.iter().skip(1).chain(objects.iter().take(1)) {
+        rows[object.y as usize][object.x as usize] = object.symbol;
+    }
+    rows.into_iter().map(|row| row.into_iter().collect::<String>() + "\n").collect()
+}
+
+// picks a monster with a chance proportional to its spawn weight
+fn choose_monster<'a>(monsters: &[(&'a structures::MonsterConfig, u32)]) -> Option<&'a structures::MonsterConfig> {
+    let total: u32 = monsters.iter().map(|&(_, weight)| weight).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut roll = gen_range(0..total);
+    for &(data, weight) in monsters {
+        if roll < weight {
+            return Some(data);
+        }
+        roll -= weight;
+    }
+    None
+}
+
+// the stats of the monster config, scaled by the difficulty of the level
+fn monster_stats(data: &structures::MonsterConfig, difficulty: &structures::DifficultyConfig) -> structures::Attackable {
+    let max_hp = structures::DifficultyConfig::scale(data.max_hp, difficulty.hp_multiplier);
+    structures::Attackable {
+        max_hp,
+        hp: max_hp,
+        armor: structures::DifficultyConfig::scale(data.armor, difficulty.armor_multiplier),
+        damage: structures::DifficultyConfig::scale(data.damage, difficulty.damage_multiplier),
+        xp: structures::DifficultyConfig::scale(data.xp, difficulty.xp_multiplier),
+        on_death: structures::DeathCallback::Monster,
+    }
+}
+
+pub fn create_monster(x: i32, y: i32, data: &structures::MonsterConfig, difficulty: &structures::DifficultyConfig) -> structures::Object {
+    let color = Color {r: data.r, g: data.g, b: data.b};
+    let mut monster = structures::Object::new(x, y, data.symbol, color, &data.name, true);
+    monster.attackable = Some(monster_stats(data, difficulty));
+    monster.alive = true;
+    monster.ai = Some(data.ai.ai());
+    monster.speed = data.speed;
+    monster.loot = data.loot.clone();
+    monster.disposition = data.disposition;
+    monster.dialogue = data.dialogue.clone();
+    //monster.always_visible = true;
+    monster
+}
+
+// gives the living monsters the looks and stats of their reloaded config, keeping their wounds
+pub fn update_monsters(objects: &mut [structures::Object], level: u32) {
+    let monsters_list = resources::monsters();
+    let difficulty_table = resources::difficulty();
+    let difficulty = match structures::DifficultyConfig::for_level(&difficulty_table, level) {
+        Some(difficulty) => difficulty,
+        None => return,
+    };
+    for object in objects.iter_mut().filter(|object| object.alive && object.ai.is_some()) {
+        if let Some(data) = monsters_list.iter().find(|data| data.name == object.name) {
+            object.symbol = data.symbol;
+            object.color = Color {r: data.r, g: data.g, b: data.b};
+            object.speed = data.speed;
+            if let Some(attackable) = object.attackable.as_mut() {
+                let stats = monster_stats(data, difficulty);
+                attackable.max_hp = stats.max_hp;
+                attackable.hp = attackable.hp.min(stats.max_hp);
+                attackable.damage = stats.damage + object.boss.as_ref().map_or(0, structures::BossState::damage_bonus);
+                attackable.armor = stats.armor;
+                attackable.xp = stats.xp;
+            }
+        }
+    }
+}
+
+pub fn is_blocked(x: i32, y: i32, map: &structures::Map, objects: &[structures::Object]) -> bool {
     if map[x as usize][y as usize].collision_enabled {
         return true;
     }
@@ -311,7 +640,7 @@ pub fn move_by(id: usize, dx: i32, dy: i32, map: &structures::Map, objects: &mut
 }
 
 
-fn initialise_fov(tcod: &mut structures::Tcod, map: &structures::Map) {
+pub fn initialise_fov(tcod: &mut structures::Tcod, map: &structures::Map) {
     for y in 0..config::MAP_HEIGHT {
         for x in 0..config::MAP_WIDTH {
             tcod.fov.set(x, y, !map[x as usize][y as usize].is_visible, !map[x as usize][y as usize].collision_enabled);
@@ -320,25 +649,59 @@ fn initialise_fov(tcod: &mut structures::Tcod, map: &structures::Map) {
 }
 
 pub fn next_level(tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &mut Vec<structures::Object>) {
-    game.messages.add("You are healing", VIOLET);
+    game.messages.add_category(structures::MessageCategory::Level, tr!("level.healing"), VIOLET);
     let heal_hp = objects[config::PLAYER].attackable.map_or(0, |f| f.max_hp / 2);
     objects[config::PLAYER].heal(heal_hp);
 
     game.level += 1;
-    game.messages.add(format!("Prepare to danger on the {} level. Monsters became stronger!", game.level), RED);
-    game.map = generate_map(objects);
-    monsters_level_up(game, objects);
+    game.messages.add_category(structures::MessageCategory::Level, tr!("level.next", level = game.level), RED);
+    game.map = generate_map(objects, game.level);
+    quests::on_level_reached(game.level, game);
     initialise_fov(tcod, &game.map);
+    bosses::enter_level(tcod, game, objects);
 }
 
-pub fn set_root(name: &str) -> tcod::console::Root {
+pub fn save_game(game: &structures::Game, objects: &[structures::Object]) -> std::io::Result<()> {
+    let serialized = serde_json::to_string(&(game, objects))?;
+    paths::create_user_dir()?;
+    write_atomic(&paths::user(config::SAVE_FILE_NAME), serialized.as_bytes())
+}
+
+pub fn load_game(path: &std::path::Path) -> Option<(structures::Game, Vec<structures::Object>)> {
+    let file = std::fs::File::open(path).ok()?;
+    let (mut game, mut objects): (structures::Game, Vec<structures::Object>) = serde_json::from_reader(file).ok()?;
+    for object in objects.iter_mut().chain(game.inventory.iter_mut()) {
+        object.refresh_name();
+    }
+    Some((game, objects))
+}
+
+// a finished run can not be continued
+pub fn delete_save() {
+    let _ = std::fs::remove_file(paths::user(config::SAVE_FILE_NAME));
+}
+
+pub fn set_root(name: &str) -> Result<tcod::console::Root, String> {
+    let font = paths::data(config::FONT_FILE_NAME);
+    if !font.is_file() {
+        return Err(format!("the font {} was not found", font.display()));
+    }
     let root = Root::initializer()
-        .font("arial10x10.png", FontLayout::Tcod)
+        .font(font, FontLayout::Tcod)
         .font_type(FontType::Greyscale)
         .size(config::SCREEN_WIDTH, config::SCREEN_HEIGHT)
         .title(name)
         .init();
-    root
+    Ok(root)
+}
+
+pub fn choose_language(root: &mut Root) {
+    let languages = locale::available_languages();
+    if languages.len() > 1 {
+        if let Some(choice) = menu(&tr!("language.header"), &languages, config::LANGUAGE_SCREEN_WIDTH, root) {
+            locale::load(&languages[choice]);
+        }
+    }
 }
 
 pub fn set_tcod(root: tcod::console::Root) -> structures::Tcod{
@@ -349,12 +712,14 @@ pub fn set_tcod(root: tcod::console::Root) -> structures::Tcod{
         screen: Offscreen::new(config::MAP_WIDTH, config::MAP_HEIGHT),  
         fov: FovMap::new(config::MAP_WIDTH, config::MAP_HEIGHT), 
         panel: Offscreen::new(config::SCREEN_WIDTH, config::PANEL_HEIGHT),
+        key: Default::default(),
+        mouse: Default::default(),
     };
     tcod
 }
 
 pub fn create_player() -> structures::Object {
-    let mut player = structures::Object::new(5, 5, '@', WHITE, "Player", true);
+    let mut player = structures::Object::translated(5, 5, '@', WHITE, structures::NameKey::new("object.player"), true);
 
     player.alive = true;
     player.attackable = Some(structures::Attackable{max_hp: 100, hp: 100, armor: 6, damage: 10, xp: 0, on_death: structures::DeathCallback::Player});
@@ -365,12 +730,19 @@ pub fn create_player() -> structures::Object {
 
 pub fn inventory_menu(inventory: &[structures::Object], header: &str, root: &mut Root) -> Option<usize> {
     let options = if inventory.len() == 0 {
-        vec!["Inventory is empty.".into()]
+        vec![tr!("inventory.empty")]
     } else {
-        inventory.iter().map(|item| item.name.clone()).collect()
+        inventory.iter().map(|item| {
+            if item.count > 1 {
+                tr!("inventory.stack", item = item.name, count = item.count)
+            } else {
+                item.name.clone()
+            }
+        }).collect()
     };
 
-    let inventory_index = menu(header, &options, config::INVENTORY_WIDTH, root);
+    let header = format!("{}{}", header, tr!("inventory.weight", weight = inventory_weight(inventory), max_weight = config::MAX_CARRY_WEIGHT));
+    let inventory_index = menu(&header, &options, config::INVENTORY_WIDTH, root);
 
     if inventory.len() > 0 {
         inventory_index
@@ -380,36 +752,257 @@ pub fn inventory_menu(inventory: &[structures::Object], header: &str, root: &mut
 }
 
 
-fn monsters_level_up(game: &mut structures::Game, objects: &mut Vec<structures::Object>) {
-    for id in 1..objects.len() {
-        if let Some(mut attackable) = objects[id].attackable.as_mut() {
-            attackable.damage += config::DAMAGE_PER_LEVEL * game.level as i32;
-            attackable.armor += config::ARMOR_PER_LEVEL * game.level as i32;
-            attackable.max_hp += config::MAX_HP_PER_LEVEL * game.level as i32;
-            attackable.hp = attackable.max_hp;
-            attackable.xp += config::XP_PER_LEVEL * game.level as i32;
-        }
-    }
-}
-
-pub fn player_move_or_attack(dx: i32, dy: i32, game: &mut structures::Game, objects: &mut [structures::Object]) {
+pub fn player_move_or_attack(dx: i32, dy: i32, tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &mut [structures::Object]) {
     let x = objects[config::PLAYER].x + dx;
     let y = objects[config::PLAYER].y + dy;
 
-    let target_id = objects.iter().position(|object| object.attackable.is_some() && object.loc() == (x, y));
-
+    let target_id = objects.iter().position(|object| object.loc() == (x, y) && (object.attackable.is_some() || object.shop.is_some() || object.dialogue.is_some()));
 
     match target_id {
-        Some(target_id) => {
+        Some(target_id) if objects[target_id].dialogue.is_some() && objects[target_id].disposition != structures::Disposition::Hostile => {
+            dialogue::talk(target_id, tcod, game, objects);
+        }
+        Some(target_id) if objects[target_id].attackable.is_some() && objects[target_id].disposition == structures::Disposition::Hostile => {
             let (player, target) = mut_two(config::PLAYER, target_id, objects);
             player.attack(target, game);
         }
-        None => {
+        Some(target_id) if objects[target_id].shop.is_some() => {
+            shop::trade(target_id, tcod, game, objects);
+        }
+        // bumping into a peaceful creature never starts a fight
+        Some(target_id) if objects[target_id].attackable.is_some() => {
+            game.messages.add(tr!("dialogue.nothing_to_say", name = objects[target_id].name), WHITE);
+        }
+        _ => {
             move_by(config::PLAYER, dx, dy, &game.map, objects);
         }
     }
 }
 
+pub fn hostile_in_view(tcod: &structures::Tcod, objects: &[structures::Object]) -> bool {
+    objects.iter().any(|object| {
+        object.ai.is_some() && object.disposition == structures::Disposition::Hostile && tcod.fov.is_in_fov(object.x, object.y)
+    })
+}
+
+pub fn start_rest(until_healed: bool, tcod: &structures::Tcod, game: &mut structures::Game, objects: &[structures::Object]) {
+    if hostile_in_view(tcod, objects) {
+        game.messages.add(tr!("rest.enemies_nearby"), RED);
+        return;
+    }
+    let hp = objects[config::PLAYER].attackable.map_or(0, |f| f.hp);
+    game.auto_action = Some(structures::AutoAction::Rest { until_healed, hp, turns: 0 });
+    game.messages.add(tr!("rest.start"), WHITE);
+}
+
+// passes the turn, slowly restoring health
+pub fn player_wait(objects: &mut [structures::Object]) {
+    objects[config::PLAYER].heal(config::REST_HEAL_AMOUNT);
+}
+
+pub fn start_travel(x: i32, y: i32, game: &mut structures::Game, objects: &[structures::Object]) {
+    let in_map = x >= 0 && y >= 0 && x < config::MAP_WIDTH && y < config::MAP_HEIGHT;
+    if !in_map || !game.map[x as usize][y as usize].is_explored || game.map[x as usize][y as usize].collision_enabled {
+        game.messages.add(tr!("travel.invalid"), RED);
+        return;
+    }
+    if objects[config::PLAYER].loc() != (x, y) {
+        game.auto_action = Some(structures::AutoAction::Travel { x, y });
+    }
+}
+
+// next step on the shortest path through explored tiles, avoiding blocking objects
+pub fn path_step(from: (i32, i32), to: (i32, i32), game: &structures::Game, objects: &[structures::Object]) -> Option<(i32, i32)> {
+    let mut known_map = FovMap::new(config::MAP_WIDTH, config::MAP_HEIGHT);
+    for y in 0..config::MAP_HEIGHT {
+        for x in 0..config::MAP_WIDTH {
+            let tile = &game.map[x as usize][y as usize];
+            known_map.set(x, y, true, tile.is_explored && !tile.collision_enabled);
+        }
+    }
+    for object in objects {
+        if object.blocks && object.loc() != from && object.loc() != to {
+            known_map.set(object.x, object.y, true, false);
+        }
+    }
+
+    let mut path = AStar::new_from_map(known_map, 1.41);
+    if path.find(from, to) {
+        path.get(0)
+    } else {
+        None
+    }
+}
+
+// first step towards the nearest explored tile which borders unexplored ones
+pub fn explore_step(game: &structures::Game, objects: &[structures::Object]) -> Option<(i32, i32)> {
+    let start = objects[config::PLAYER].loc();
+    let mut came_from = vec![vec![None; config::MAP_HEIGHT as usize]; config::MAP_WIDTH as usize];
+    came_from[start.0 as usize][start.1 as usize] = Some(start);
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+
+    while let Some((x, y)) = queue.pop_front() {
+        if (x, y) != start && is_frontier(x, y, &game.map) {
+            // walk back to find the step taken from the start
+            let mut step = (x, y);
+            while let Some(previous) = came_from[step.0 as usize][step.1 as usize] {
+                if previous == start {
+                    return Some(step);
+                }
+                step = previous;
+            }
+        }
+        for dx in -1..2 {
+            for dy in -1..2 {
+                let (next_x, next_y) = (x + dx, y + dy);
+                if next_x < 0 || next_y < 0 || next_x >= config::MAP_WIDTH || next_y >= config::MAP_HEIGHT {
+                    continue;
+                }
+                let tile = &game.map[next_x as usize][next_y as usize];
+                if came_from[next_x as usize][next_y as usize].is_some() || !tile.is_explored || is_blocked(next_x, next_y, &game.map, objects) {
+                    continue;
+                }
+                came_from[next_x as usize][next_y as usize] = Some((x, y));
+                queue.push_back((next_x, next_y));
+            }
+        }
+    }
+    None
+}
+
+fn is_frontier(x: i32, y: i32, map: &structures::Map) -> bool {
+    for dx in -1..2 {
+        for dy in -1..2 {
+            let (next_x, next_y) = (x + dx, y + dy);
+            if next_x >= 0 && next_y >= 0 && next_x < config::MAP_WIDTH && next_y < config::MAP_HEIGHT
+                && !map[next_x as usize][next_y as usize].is_explored {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+pub fn look(tcod: &structures::Tcod, game: &mut structures::Game, objects: &[structures::Object]) {
+    let names: Vec<&str> = objects.iter().enumerate()
+        .filter(|&(id, object)| id != config::PLAYER && tcod.fov.is_in_fov(object.x, object.y))
+        .map(|(_, object)| object.name.as_str())
+        .collect();
+    if names.is_empty() {
+        game.messages.add(tr!("look.nothing"), WHITE);
+    } else {
+        game.messages.add(tr!("look.objects", names = names.join(", ")), WHITE);
+    }
+    let monsters_list = resources::monsters();
+    let mut described = vec![];
+    for name in names {
+        if let Some(data) = monsters_list.iter().find(|data| data.name == name && !data.description.is_empty()) {
+            if !described.contains(&name) {
+                described.push(name);
+                game.messages.add(tr!("look.description", name = name, description = data.description), LIGHT_GREY);
+            }
+        }
+    }
+}
+
+// what the message log shows: category filter, search text and how far it is scrolled back
+#[derive(Default)]
+struct LogView {
+    filter: Option<structures::MessageCategory>,
+    search: String,
+    searching: bool,
+    scroll: i32,
+}
+
+impl LogView {
+    // applies one key event, returns true when the log should be closed
+    fn handle_key(&mut self, code: tcod::input::KeyCode, text: &str, page: i32) -> bool {
+        use tcod::input::KeyCode::*;
+
+        if self.searching {
+            match code {
+                Enter | Escape => self.searching = false,
+                Backspace => { self.search.pop(); }
+                Text => self.search.push_str(text),
+                _ => {}
+            }
+            return false;
+        }
+        match code {
+            Escape => return true,
+            Up => self.scroll += 1,
+            Down => self.scroll -= 1,
+            PageUp => self.scroll += page,
+            PageDown => self.scroll -= page,
+            Tab => {
+                self.filter = match self.filter {
+                    None => Some(structures::MessageCategory::ALL[0]),
+                    Some(category) => structures::MessageCategory::ALL.iter()
+                        .position(|&other| other == category)
+                        .and_then(|index| structures::MessageCategory::ALL.get(index + 1).copied()),
+                };
+                self.scroll = 0;
+            }
+            Text if text == "/" => {
+                self.searching = true;
+                self.search.clear();
+                self.scroll = 0;
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn shows(&self, message: &structures::Message) -> bool {
+        self.filter.is_none_or(|category| message.category == category)
+            && (self.search.is_empty() || message.text.to_lowercase().contains(&self.search.to_lowercase()))
+    }
+}
+
+// full-screen message history with scrolling, category filter and search
+pub fn message_log(messages: &structures::Messages, root: &mut Root) {
+    let mut view = LogView::default();
+    let page = config::SCREEN_HEIGHT - 2;
+    let mut window = Offscreen::new(config::SCREEN_WIDTH, config::SCREEN_HEIGHT);
+
+    loop {
+        let lines: Vec<&structures::Message> = messages.iter().filter(|message| view.shows(message)).collect();
+        let max_scroll = cmp::max(lines.len() as i32 - page, 0);
+        view.scroll = cmp::min(cmp::max(view.scroll, 0), max_scroll);
+
+        window.set_default_background(BLACK);
+        window.clear();
+        window.set_default_foreground(WHITE);
+        let filter_name = view.filter.map_or(tr!("category.All"), |category| locale::tr(&format!("category.{:?}", category)));
+        let search_text = if view.searching { format!("{}_", view.search) } else { view.search.clone() };
+        window.print_ex(0, 0, BackgroundFlag::None, TextAlignment::Left,
+            tr!("message_log.header", filter = filter_name, search = search_text));
+
+        // newest messages at the bottom, scrolling moves back in history
+        let end = lines.len() as i32 - view.scroll;
+        let start = cmp::max(end - page, 0);
+        for (row, message) in lines[start as usize..end as usize].iter().enumerate() {
+            window.set_default_foreground(message.color);
+            window.print_ex(0, row as i32 + 2, BackgroundFlag::None, TextAlignment::Left, message.display());
+        }
+
+        blit(&window, (0, 0), (config::SCREEN_WIDTH, config::SCREEN_HEIGHT), root, (0, 0), 1.0, 1.0);
+        root.flush();
+
+        // no flush, letters and '/' only arrive in the text event that follows the key press
+        let key = root.wait_for_keypress(false);
+        if view.handle_key(key.code, key.text(), page) {
+            break;
+        }
+    }
+}
+
+// drops the key events still waiting, like the text event that follows every printable key press
+pub fn flush_input() {
+    while tcod::input::check_for_event(tcod::input::KEY_PRESS).is_some() {}
+}
+
 pub fn msgbox(text: &str, width: i32, root: &mut Root) {
     let options: &[&str] = &[];
     menu(text, options, width, root);
@@ -417,6 +1010,26 @@ pub fn msgbox(text: &str, width: i32, root: &mut Root) {
 
 
 pub fn menu<T: AsRef<str>>(header: &str, options: &[T], width: i32, root: &mut Root) -> Option<usize> {
+    draw_menu(header, options, width, root);
+    let key = root.wait_for_keypress(true);
+    // the text event of a letter is still queued and would reach the game as a command
+    flush_input();
+
+    // convert the ASCII code to an index; if it corresponds to an option, return it
+    if key.printable.is_alphabetic() {
+        let index = key.printable.to_ascii_lowercase() as usize - 'a' as usize;
+        if index < options.len() {
+            Some(index)
+        } else {
+            None
+        }
+    } else {
+        None
+    }
+}
+
+// draws the menu window and presents it without waiting for the player
+pub fn draw_menu<T: AsRef<str>>(header: &str, options: &[T], width: i32, root: &mut Root) {
     assert!(options.len() <= 26, "Cannot have a menu with more than 26 options.");
 
     // calculate total height for the header (after auto-wrap) and one line per option
@@ -442,21 +1055,8 @@ pub fn menu<T: AsRef<str>>(header: &str, options: &[T], width: i32, root: &mut R
     let y = config::SCREEN_HEIGHT / 2 - height / 2;
     blit(&window, (0, 0), (width, height), root, (x, y), 1.0, 0.7);
 
-    // present the root console to the player and wait for a key-press
+    // present the root console to the player
     root.flush();
-    let key = root.wait_for_keypress(true);
-
-    // convert the ASCII code to an index; if it corresponds to an option, return it
-    if key.printable.is_alphabetic() {
-        let index = key.printable.to_ascii_lowercase() as usize - 'a' as usize;
-        if index < options.len() {
-            Some(index)
-        } else {
-            None
-        }
-    } else {
-        None
-    }
 }
 
 
@@ -482,83 +1082,302 @@ pub fn monster_move(id: usize, player_x: i32, player_y: i32, map: &structures::M
     move_by(id, dx, dy, map, objects);
 }
 
-pub fn write_map(data: &map_editor::HelloState) {
-
-    let map_config = structures::MapConfig {
-        light_wall_r: data.light_wall_color_r.parse::<u8>().unwrap(),
-        light_wall_g: data.light_wall_color_g.parse::<u8>().unwrap(),
-        light_wall_b: data.light_wall_color_b.parse::<u8>().unwrap(),
-        dark_wall_r: data.dark_wall_color_r.parse::<u8>().unwrap(),
-        dark_wall_g: data.dark_wall_color_g.parse::<u8>().unwrap(),
-        dark_wall_b: data.dark_wall_color_b.parse::<u8>().unwrap(),
-        light_ground_r: data.light_ground_color_r.parse::<u8>().unwrap(),
-        light_ground_g: data.light_ground_color_g.parse::<u8>().unwrap(),
-        light_ground_b: data.light_ground_color_b.parse::<u8>().unwrap(),
-        dark_ground_r: data.dark_ground_color_r.parse::<u8>().unwrap(),
-        dark_ground_g: data.dark_ground_color_g.parse::<u8>().unwrap(),
-        dark_ground_b: data.dark_ground_color_b.parse::<u8>().unwrap(),   
+fn parse_field<T: std::str::FromStr>(field: &'static str, value: &str) -> Result<T, structures::ConfigError> {
+    value.trim().parse::<T>().map_err(|_| structures::ConfigError::InvalidNumber { field, value: value.to_string() })
+}
+
+// the config is written in the format of its existing file, new files are json
+fn write_config_file<T: serde::Serialize>(file_name: &str, json: &structures::ConfigFile<T>) -> Result<(), structures::ConfigError> {
+    write_config_path(config_file_path(file_name), json)
+}
+
+fn write_config_path<T: serde::Serialize>(path: PathBuf, json: &structures::ConfigFile<T>) -> Result<(), structures::ConfigError> {
+    let serialized = match structures::ConfigFormat::from_path(&path) {
+        structures::ConfigFormat::Json => serde_json::to_string_pretty(json).map_err(|error| error.to_string()),
+        // going through a toml value puts plain values before tables, as toml requires
+        structures::ConfigFormat::Toml => toml::Value::try_from(json).map(|value| value.to_string()).map_err(|error| error.to_string()),
+        structures::ConfigFormat::Ron => ron::ser::to_string_pretty(json, ron::ser::PrettyConfig::default()).map_err(|error| error.to_string()),
     };
+    let serialized = serialized.map_err(|message| structures::ConfigError::Parse { path: path.clone(), message })?;
+    write_atomic(&path, serialized.as_bytes()).map_err(|error| structures::ConfigError::Io { path, error })
+}
 
-    let mut my_file = std::fs::File::create(config::CONFIG_MAP_FILE_NAME).expect("creation failed");
-    let map_config_json = structures::MapConfigJson {
-        saved_configs: map_config,
+// writes a temporary file next to the target and renames it over the target, so that a crash
+// in the middle of writing never leaves a half written file behind
+pub fn write_atomic(path: &std::path::Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let mut my_file = std::fs::File::create(&temp_path)?;
+    my_file.write_all(contents)?;
+    my_file.sync_all()?;
+    std::fs::rename(&temp_path, path)
+}
+
+// the palette typed in the map editor, also used for its preview
+pub fn map_from_form(data: &map_editor::HelloState) -> Result<structures::MapConfig, structures::ConfigError> {
+    Ok(structures::MapConfig {
+        light_wall_r: parse_field("light wall red", &data.light_wall_color_r)?,
+        light_wall_g: parse_field("light wall green", &data.light_wall_color_g)?,
+        light_wall_b: parse_field("light wall blue", &data.light_wall_color_b)?,
+        dark_wall_r: parse_field("dark wall red", &data.dark_wall_color_r)?,
+        dark_wall_g: parse_field("dark wall green", &data.dark_wall_color_g)?,
+        dark_wall_b: parse_field("dark wall blue", &data.dark_wall_color_b)?,
+        light_ground_r: parse_field("light ground red", &data.light_ground_color_r)?,
+        light_ground_g: parse_field("light ground green", &data.light_ground_color_g)?,
+        light_ground_b: parse_field("light ground blue", &data.light_ground_color_b)?,
+        dark_ground_r: parse_field("dark ground red", &data.dark_ground_color_r)?,
+        dark_ground_g: parse_field("dark ground green", &data.dark_ground_color_g)?,
+        dark_ground_b: parse_field("dark ground blue", &data.dark_ground_color_b)?,
+    })
+}
+
+pub fn write_map(data: &map_editor::HelloState) -> Result<(), structures::ConfigError> {
+    let map_config = map_from_form(data)?;
+    let errors = validation::validate_map(&map_config);
+    let map_config = check_config(config::CONFIG_MAP_FILE_NAME, map_config, errors)?;
+    write_config_file(config::CONFIG_MAP_FILE_NAME, &structures::MapConfigJson::new(map_config))
+}
+
+// saves the monster of the editor form, over the monster at `index` or as a new one,
+// returning where it ended up in the list
+pub fn write_monster(data: &editor::HelloState, index: Option<usize>) -> Result<usize, structures::ConfigError> {
+
+    let symbol = data.symbol.trim().chars().next().ok_or(structures::ConfigError::EmptyField("symbol"))?;
+    if data.symbol.trim().chars().count() > 1 {
+        return Err(structures::ConfigError::Invalid {
+            path: config_file_path(config::CONFIG_MONSTER_FILE_NAME),
+            errors: vec![structures::ValidationError { entry: name_or_new(&data.name), message: "symbol must be a single character".into() }],
+        });
+    }
+    let name = data.name.trim();
+    if name.is_empty() {
+        return Err(structures::ConfigError::EmptyField("name"));
+    }
+    let mut monster = structures::MonsterConfig { 
+        symbol,
+        name: name.to_string(),
+        max_hp: parse_field("max HP", &data.max_hp)?,
+        damage: parse_field("damage", &data.damage)?,
+        armor: parse_field("armor", &data.armor)?,
+        r: parse_field("red", &data.r)?,
+        g: parse_field("green", &data.g)?,
+        b: parse_field("blue", &data.b)?,
+        loot: vec![],
+        disposition: structures::Disposition::Hostile,
+        dialogue: None,
+        xp: parse_field("xp", &data.xp)?,
+        ai: structures::AiKind::ALL.iter().copied().find(|kind| kind.name() == data.ai).unwrap_or_default(),
+        speed: parse_field("speed", &data.speed)?,
+        spawn_weight: parse_field("spawn weight", &data.spawn_weight)?,
+        min_depth: parse_field("min depth", &data.min_depth)?,
+        max_depth: if data.max_depth.trim().is_empty() { None } else { Some(parse_field("max depth", &data.max_depth)?) },
+        min_group: parse_field("min group size", &data.min_group)?,
+        max_group: parse_field("max group size", &data.max_group)?,
+        description: data.description.trim().to_string(),
+        boss: None,
     };
-    let serialized = serde_json::to_string(&map_config_json).unwrap();
-    my_file.write(serialized.as_bytes()).unwrap();
+
+    let mut monsters = read_monster_list()?;
+    let index = match index.filter(|&index| index < monsters.len()) {
+        Some(index) => {
+            // the form does not show loot, disposition and dialogue, they are kept as they were
+            let old = &mut monsters[index];
+            monster.loot = std::mem::take(&mut old.loot);
+            monster.disposition = old.disposition;
+            monster.dialogue = old.dialogue.take();
+            monster.boss = old.boss.take();
+            monsters[index] = monster;
+            index
+        }
+        None => {
+            monsters.push(monster);
+            monsters.len() - 1
+        }
+    };
+    write_monster_list(monsters)?;
+    Ok(index)
 }
 
-pub fn write_monster(data: &editor::HelloState) {
+pub fn delete_monster(index: usize) -> Result<(), structures::ConfigError> {
+    let mut monsters = read_monster_list()?;
+    if index < monsters.len() {
+        monsters.remove(index);
+    }
+    write_monster_list(monsters)
+}
 
-    let monster = structures::MonsterConfig { 
-        symbol: data.symbol.chars().nth(0).unwrap() as char, 
-        name: data.name.to_string(),
-        max_hp: data.max_hp.parse::<i32>().unwrap(),
-        damage: data.damage.parse::<i32>().unwrap(),
-        armor: data.armor.parse::<i32>().unwrap(),
-        r: data.r.parse::<u8>().unwrap(),
-        g: data.g.parse::<u8>().unwrap(),
-        b: data.b.parse::<u8>().unwrap(),
+// copies the monster under a free name right after the original, returning the index of the copy
+pub fn duplicate_monster(index: usize) -> Result<usize, structures::ConfigError> {
+    let mut monsters = read_monster_list()?;
+    let mut copy = match monsters.get(index) {
+        Some(monster) => monster.clone(),
+        None => return Ok(index),
     };
+    copy.name = (1..)
+        .map(|number| if number == 1 { format!("{} copy", copy.name) } else { format!("{} copy {}", copy.name, number) })
+        .find(|name| !monsters.iter().any(|monster| &monster.name == name))
+        .unwrap();
+    monsters.insert(index + 1, copy);
+    write_monster_list(monsters)?;
+    Ok(index + 1)
+}
 
-    if !Path::new(config::CONFIG_MONSTER_FILE_NAME).exists(){
-        let mut my_file = std::fs::File::create(config::CONFIG_MONSTER_FILE_NAME).expect("creation failed");
-        let monster_config_list = vec![monster];
-        let monster_config_json = structures::MonsterConfigJson {
-            saved_configs: monster_config_list,
-        };
-        let serialized = serde_json::to_string(&monster_config_json).unwrap();
-        my_file.write(serialized.as_bytes()).unwrap();
+// moves the monster one place up or down the list, returning its new index
+pub fn move_monster(index: usize, up: bool) -> Result<usize, structures::ConfigError> {
+    let mut monsters = read_monster_list()?;
+    let target = if up { index.checked_sub(1) } else { Some(index + 1) };
+    match target.filter(|&target| target < monsters.len() && index < monsters.len()) {
+        Some(target) => {
+            monsters.swap(index, target);
+            write_monster_list(monsters)?;
+            Ok(target)
+        }
+        None => Ok(index),
+    }
+}
 
-    } else {
-        let my_existing_file = std::fs::File::open(config::CONFIG_MONSTER_FILE_NAME).unwrap();
-        let json = ajson::parse_from_read(my_existing_file).unwrap();   
-        let monsters_list = json.get("saved_configs").unwrap();
-        let mut deserialized: Vec<structures::MonsterConfig> = serde_json::from_str(&monsters_list.as_str()).unwrap();
-        deserialized.push(monster);
-        let monster_config_json = structures::MonsterConfigJson {
-            saved_configs: deserialized,
-        };
-        let mut my_file = std::fs::File::create(config::CONFIG_MONSTER_FILE_NAME).expect("creation failed");
-        let serialized = serde_json::to_string(&monster_config_json).unwrap();
-        my_file.write(serialized.as_bytes()).unwrap();
-    }
-     //let deserialized: Vec<structures::MonsterConfig> = serde_json::from_str(&serialized).unwrap();
-    // //my_file.write_all("Hello Chercher.tech".as_bytes()).expect("write failed");
-    // my_file.write(data.name.as_bytes()).expect("Name write failed");
-    // //my_file.write_all("	 Learning is Fun".as_bytes()).expect("write failed");
-    // println!("The tag line of Chercher.tech has been added, open the file to see :)");
-    
-    
+// the monsters as they are in the file, not validated so that the editor can show and fix broken entries
+pub fn read_monster_list() -> Result<Vec<structures::MonsterConfig>, structures::ConfigError> {
+    // a missing file is started anew, a broken one is left for the user to fix
+    match read_config_file::<Vec<structures::MonsterConfig>>(config::CONFIG_MONSTER_FILE_NAME) {
+        Ok(json) => Ok(json.saved_configs),
+        Err(structures::ConfigError::Io { error, .. }) if error.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+        Err(error) => Err(error),
+    }
+}
 
+fn write_monster_list(monsters: Vec<structures::MonsterConfig>) -> Result<(), structures::ConfigError> {
+    // the whole list is checked, so that a duplicate of an existing name is found too
+    let errors = validation::validate_monsters(&monsters);
+    let monsters = check_config(config::CONFIG_MONSTER_FILE_NAME, monsters, errors)?;
+    write_config_file(config::CONFIG_MONSTER_FILE_NAME, &structures::MonsterConfigJson::new(monsters))
+}
 
-    // my_file.write(serialized.as_bytes());
+fn name_or_new(name: &str) -> String {
+    if name.trim().is_empty() { "new monster".into() } else { format!("monster '{}'", name.trim()) }
+}
 
-    // println!("Serialized: {}", serialized);
-    // let deserialized: structures::MonsterConfig = serde_json::from_str(&serialized).unwrap();
-    // println!("Deserialized: {:?}", deserialized);
+pub fn remove() -> Result<(), structures::ConfigError> {
+    let path = config_file_path(config::CONFIG_MONSTER_FILE_NAME);
+    std::fs::remove_file(&path).map_err(|error| structures::ConfigError::Io { path, error })
 }
 
-pub fn remove() {
-    std::fs::remove_file(config::CONFIG_MONSTER_FILE_NAME).expect("could not remove file");
-    println!("The file has been removed !");
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tcod::input::KeyCode;
+
+    // a fresh directory for the files of one test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("draguation_work_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn test_monsters() -> Vec<structures::MonsterConfig> {
+        let mut monsters = structures::MonsterConfig::defaults();
+        monsters[0].loot = vec![structures::LootConfig { item: structures::Item::Heal, chance: 0.5, min_count: 1, max_count: 2, depth_bonus: 0.1 }];
+        monsters[1].max_depth = Some(4);
+        monsters[1].boss = Some(structures::BossConfig {
+            depth: 3,
+            abilities: vec![
+                structures::BossAbility::Regenerate { amount: 1 },
+                structures::BossAbility::Summon { monster: "Orc".into(), every_turns: 6, max: 3 },
+                structures::BossAbility::Enrage { below_hp_fraction: 0.5, damage_bonus: 4 },
+            ],
+            guaranteed_loot: vec![structures::GuaranteedLoot { item: structures::Item::Gold, count: 100 }],
+            announcement: "The troll blocks the way.".into(),
+        });
+        monsters
+    }
+
+    fn message(text: &str) -> structures::Message {
+        structures::Message { text: text.into(), color: WHITE, category: structures::MessageCategory::Combat, count: 1 }
+    }
+
+    #[test]
+    fn message_log_search_is_typed_from_text_events() {
+        let mut view = LogView::default();
+        // a letter arrives as a key press without text followed by its text event
+        for (code, text) in &[(KeyCode::Char, ""), (KeyCode::Text, "/"), (KeyCode::Char, ""), (KeyCode::Text, "o"),
+                              (KeyCode::Text, "r"), (KeyCode::Text, "x"), (KeyCode::Backspace, "")] {
+            assert!(!view.handle_key(*code, text, 10));
+        }
+        assert!(view.searching);
+        assert_eq!(view.search, "or");
+        assert!(view.shows(&message("The Orc hits you")));
+        assert!(!view.shows(&message("You hit the troll")));
+
+        assert!(!view.handle_key(KeyCode::Enter, "", 10));
+        assert!(!view.searching);
+        assert_eq!(view.search, "or");
+        assert!(view.handle_key(KeyCode::Escape, "", 10));
+    }
+
+    #[test]
+    fn message_log_escape_leaves_search_before_closing() {
+        let mut view = LogView::default();
+        view.handle_key(KeyCode::Text, "/", 10);
+        assert!(!view.handle_key(KeyCode::Escape, "", 10));
+        assert!(!view.searching);
+        assert!(view.handle_key(KeyCode::Escape, "", 10));
+    }
+
+    #[test]
+    fn migrate_accepts_every_version_up_to_the_current_one() {
+        let path = PathBuf::from("monsters.json");
+        for version in 0..=config::CONFIG_VERSION {
+            let json = structures::ConfigFile { version, saved_configs: 7 };
+            assert_eq!(migrate(json, path.clone()).unwrap().saved_configs, 7);
+        }
+        let newer = structures::ConfigFile { version: config::CONFIG_VERSION + 1, saved_configs: 7 };
+        match migrate(newer, path) {
+            Err(structures::ConfigError::UnsupportedVersion { version, .. }) => assert_eq!(version, config::CONFIG_VERSION + 1),
+            other => panic!("expected an unsupported version, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unversioned_files_are_read_as_version_0() {
+        let path = test_dir("unversioned").join("map.json");
+        std::fs::write(&path, r#"{"saved_configs": [1, 2]}"#).unwrap();
+        let json: structures::ConfigFile<Vec<i32>> = read_config_path(path).unwrap();
+        assert_eq!(json.version, 0);
+        assert_eq!(json.saved_configs, vec![1, 2]);
+    }
+
+    #[test]
+    fn newer_files_and_broken_files_are_rejected() {
+        let dir = test_dir("rejected");
+        let newer = dir.join("newer.json");
+        std::fs::write(&newer, format!(r#"{{"version": {}, "saved_configs": []}}"#, config::CONFIG_VERSION + 1)).unwrap();
+        assert!(matches!(read_config_path::<Vec<i32>>(newer), Err(structures::ConfigError::UnsupportedVersion { .. })));
+
+        for (file_name, text) in &[("broken.json", "{"), ("broken.toml", "version = "), ("broken.ron", "(version: ")] {
+            let path = dir.join(file_name);
+            std::fs::write(&path, text).unwrap();
+            assert!(matches!(read_config_path::<Vec<i32>>(path), Err(structures::ConfigError::Parse { .. })), "{} should not parse", file_name);
+        }
+        assert!(matches!(read_config_path::<Vec<i32>>(dir.join("missing.json")), Err(structures::ConfigError::Io { .. })));
+    }
+
+    #[test]
+    fn configs_round_trip_in_every_format() {
+        let dir = test_dir("round_trip");
+        for format in structures::ConfigFormat::ALL.iter() {
+            let path = dir.join(format!("monsters.{}", format.extension()));
+            write_config_path(path.clone(), &structures::MonsterConfigJson::new(test_monsters())).unwrap();
+            let json: structures::MonsterConfigJson = read_config_path(path).unwrap();
+            assert_eq!(json.version, config::CONFIG_VERSION, "{:?}", format);
+            assert_eq!(serde_json::to_value(&json.saved_configs).unwrap(), serde_json::to_value(test_monsters()).unwrap(), "{:?}", format);
+        }
+        // the other configs go through the same functions
+        let path = dir.join("difficulty.toml");
+        write_config_path(path.clone(), &structures::DifficultyConfigJson::new(structures::DifficultyConfig::defaults())).unwrap();
+        let json: structures::DifficultyConfigJson = read_config_path(path).unwrap();
+        assert_eq!(json.saved_configs[0].max_room_monsters, config::MAX_ROOM_MONSTERS);
+    }
+}
@@ -0,0 +1,129 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::config as config; // change for other game
+
+
+// where the game looks for its files:
+// data - font and string tables, shipped with the game
+// config - monster, map, dialogue and quest configs, changed by the editors
+// user - saves and key bindings, written while playing
+pub struct Paths {
+    pub data_dir: PathBuf,
+    pub config_dir: PathBuf,
+    pub user_dir: PathBuf,
+}
+
+static PATHS: OnceLock<Paths> = OnceLock::new();
+
+// resolves the directories, the flags of the command line take priority over the environment
+pub fn init(data_dir: Option<PathBuf>, config_dir: Option<PathBuf>) -> Result<(), String> {
+    let data_dir = find_data_dir(data_dir)?;
+    set(data_dir, config_dir)
+}
+
+// resolves the directories for the commands that only read configs, which work without the game data:
+// the configs are then read from the working directory unless a config directory is given
+pub fn init_config(data_dir: Option<PathBuf>, config_dir: Option<PathBuf>) -> Result<(), String> {
+    let data_dir = find_data_dir(data_dir).unwrap_or_else(|_| PathBuf::from("."));
+    set(data_dir, config_dir)
+}
+
+fn set(data_dir: PathBuf, config_dir: Option<PathBuf>) -> Result<(), String> {
+    let config_dir = match config_dir.or_else(|| env_dir(config::CONFIG_DIR_ENV)) {
+        Some(dir) if dir.is_dir() => dir,
+        Some(dir) => return Err(format!("config directory {} does not exist", dir.display())),
+        None => xdg_dir("XDG_CONFIG_HOME", ".config").filter(|dir| dir.is_dir()).unwrap_or_else(|| data_dir.clone()),
+    };
+    let user_dir = env_dir(config::USER_DIR_ENV)
+        .or_else(|| xdg_dir("XDG_DATA_HOME", ".local/share"))
+        .unwrap_or_else(|| data_dir.clone());
+
+    let _ = PATHS.set(Paths { data_dir, config_dir, user_dir });
+    Ok(())
+}
+
+fn find_data_dir(flag: Option<PathBuf>) -> Result<PathBuf, String> {
+    // a directory given explicitly has to contain the data
+    if let Some(dir) = flag.or_else(|| env_dir(config::DATA_DIR_ENV)) {
+        return if has_data(&dir) {
+            Ok(dir)
+        } else {
+            Err(format!("{} was not found in the data directory {}", config::LANGUAGE_DIR, dir.display()))
+        };
+    }
+
+    let mut candidates = vec![];
+    candidates.extend(xdg_dir("XDG_DATA_HOME", ".local/share"));
+    let data_dirs = env::var("XDG_DATA_DIRS").unwrap_or_else(|_| "/usr/local/share:/usr/share".into());
+    candidates.extend(env::split_paths(&data_dirs).map(|dir| dir.join(config::APP_DIR_NAME)));
+    if let Some(exe_dir) = env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+        candidates.push(exe_dir);
+    }
+    if let Ok(current_dir) = env::current_dir() {
+        candidates.push(current_dir);
+    }
+
+    match candidates.iter().find(|dir| has_data(dir)) {
+        Some(dir) => Ok(dir.clone()),
+        None => {
+            let searched: Vec<String> = candidates.iter().map(|dir| format!("    {}", dir.display())).collect();
+            Err(format!(
+                "could not find the game data ({}), searched in:\n{}\nuse --data-dir or set {}",
+                config::LANGUAGE_DIR,
+                searched.join("\n"),
+                config::DATA_DIR_ENV,
+            ))
+        }
+    }
+}
+
+// the font is checked when the window is opened, see myengine::set_root
+fn has_data(dir: &Path) -> bool {
+    dir.join(config::LANGUAGE_DIR).is_dir()
+}
+
+fn env_dir(name: &str) -> Option<PathBuf> {
+    env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from)
+}
+
+// the game directory inside an XDG base directory, falling back to its default under the home directory
+fn xdg_dir(variable: &str, home_default: &str) -> Option<PathBuf> {
+    env_dir(variable)
+        .or_else(|| env_dir("HOME").map(|home| home.join(home_default)))
+        .map(|dir| dir.join(config::APP_DIR_NAME))
+}
+
+// before `init` every path is relative to the working directory
+fn paths() -> Option<&'static Paths> {
+    PATHS.get()
+}
+
+pub fn data(file_name: &str) -> PathBuf {
+    paths().map_or_else(|| PathBuf::from(file_name), |paths| paths.data_dir.join(file_name))
+}
+
+pub fn config(file_name: &str) -> PathBuf {
+    paths().map_or_else(|| PathBuf::from(file_name), |paths| paths.config_dir.join(file_name))
+}
+
+pub fn user(file_name: &str) -> PathBuf {
+    paths().map_or_else(|| PathBuf::from(file_name), |paths| paths.user_dir.join(file_name))
+}
+
+// the user directory is created on the first write
+pub fn create_user_dir() -> std::io::Result<()> {
+    match paths() {
+        Some(paths) => std::fs::create_dir_all(&paths.user_dir),
+        None => Ok(()),
+    }
+}
+
+pub fn data_dir() -> Option<&'static Path> {
+    paths().map(|paths| paths.data_dir.as_path())
+}
+
+pub fn config_dir() -> Option<&'static Path> {
+    paths().map(|paths| paths.config_dir.as_path())
+}
@@ -0,0 +1,138 @@
+use tcod::colors::*;
+use tcod::console::*;
+
+use crate::structures;
+use crate::myengine;
+use crate::resources;
+use crate::config as config; // change for other game
+
+
+pub fn starting_quests() -> Vec<structures::Quest> {
+    resources::quests()
+        .iter()
+        .filter(|quest| quest.auto_start)
+        .cloned()
+        .map(new_quest)
+        .collect()
+}
+
+fn new_quest(config: structures::QuestConfig) -> structures::Quest {
+    structures::Quest { config, progress: 0, state: structures::QuestState::Active }
+}
+
+pub fn give_quest(quest_id: &str, game: &mut structures::Game) {
+    if game.quests.iter().any(|quest| quest.config.id == quest_id) {
+        game.messages.add(tr!("quest.already_have"), WHITE);
+        return;
+    }
+    match resources::quests().iter().find(|quest| quest.id == quest_id).cloned() {
+        Some(quest) => {
+            game.messages.add(tr!("quest.new", name = quest.name), LIGHT_YELLOW);
+            game.quests.push(new_quest(quest));
+            // the player may already carry the item or stand on the level
+            check_inventory(game);
+            on_level_reached(game.level, game);
+        }
+        None => game.messages.add(tr!("quest.unknown", id = quest_id), RED),
+    }
+}
+
+pub fn spawn_quest_giver(room: structures::Rect, map: &structures::Map, objects: &mut Vec<structures::Object>) {
+    let x = myengine::gen_range(room.x1 + 1..room.x2);
+    let y = myengine::gen_range(room.y1 + 1..room.y2);
+    if myengine::is_blocked(x, y, map, objects) {
+        return;
+    }
+
+    let mut hermit = structures::Object::translated(x, y, '@', LIGHT_GREEN, structures::NameKey::new("npc.hermit"), true);
+    hermit.always_visible = true;
+    hermit.disposition = structures::Disposition::Friendly;
+    hermit.dialogue = Some("hermit".into());
+    objects.push(hermit);
+}
+
+pub fn on_monster_killed(name: &str, game: &mut structures::Game) {
+    advance(game, |objective, progress| match objective {
+        structures::Objective::Kill { monster, .. } if monster == name => Some(progress + 1),
+        _ => None,
+    });
+}
+
+pub fn on_item_picked(item: structures::Item, game: &mut structures::Game) {
+    advance(game, |objective, progress| match objective {
+        structures::Objective::Retrieve { item: wanted } if *wanted == item => Some(progress + 1),
+        _ => None,
+    });
+}
+
+pub fn on_level_reached(level: u32, game: &mut structures::Game) {
+    advance(game, |objective, _| match objective {
+        structures::Objective::ReachLevel { .. } => Some(level as i32),
+        _ => None,
+    });
+}
+
+pub fn on_talked(dialogue_id: &str, game: &mut structures::Game) {
+    advance(game, |objective, progress| match objective {
+        structures::Objective::TalkTo { npc } if npc == dialogue_id => Some(progress + 1),
+        _ => None,
+    });
+    check_inventory(game);
+}
+
+// completes the retrieve quests whose item is already in the inventory
+fn check_inventory(game: &mut structures::Game) {
+    let carried: Vec<structures::Item> = game.inventory.iter().filter_map(|object| object.item).collect();
+    advance(game, |objective, _| match objective {
+        structures::Objective::Retrieve { item } if carried.contains(item) => Some(1),
+        _ => None,
+    });
+}
+
+// updates the progress of every active quest whose objective matches the event
+fn advance<F: Fn(&structures::Objective, i32) -> Option<i32>>(game: &mut structures::Game, update: F) {
+    for quest in game.quests.iter_mut().filter(|quest| quest.state == structures::QuestState::Active) {
+        if let Some(progress) = update(&quest.config.objective, quest.progress) {
+            quest.progress = progress;
+            if quest.progress >= quest.config.objective.target() {
+                quest.state = structures::QuestState::Completed;
+                game.messages.add(tr!("quest.completed", name = quest.config.name), LIGHT_YELLOW);
+            }
+        }
+    }
+}
+
+pub fn claim_rewards(game: &mut structures::Game, objects: &mut Vec<structures::Object>) {
+    let mut rewards = vec![];
+    for quest in game.quests.iter_mut().filter(|quest| quest.state == structures::QuestState::Completed) {
+        quest.state = structures::QuestState::Rewarded;
+        rewards.push(quest.config.reward.clone());
+    }
+
+    for reward in rewards {
+        if let Some(attackable) = objects[config::PLAYER].attackable.as_mut() {
+            attackable.xp += reward.xp;
+        }
+        game.gold += reward.gold;
+        game.messages.add_category(structures::MessageCategory::Loot, tr!("quest.reward", xp = reward.xp, gold = reward.gold), LIGHT_YELLOW);
+        // reward items are picked up like loot, so gold is counted and items too heavy stay on the floor
+        let (x, y) = objects[config::PLAYER].loc();
+        for item in reward.items {
+            objects.push(structures::Object::new_item(x, y, item));
+            myengine::pick_item_up(objects.len() - 1, game, objects);
+        }
+    }
+}
+
+pub fn quest_log(game: &structures::Game, root: &mut Root) {
+    let mut text = tr!("quest.log_header");
+    if game.quests.is_empty() {
+        text.push_str(&tr!("quest.log_empty"));
+    }
+    for quest in &game.quests {
+        let mark = if quest.state == structures::QuestState::Active { ' ' } else { 'x' };
+        let progress = quest.progress.min(quest.config.objective.target());
+        text.push_str(&format!("[{}] {} ({}/{})\n    {}\n", mark, quest.config.name, progress, quest.config.objective.target(), quest.config.description));
+    }
+    myengine::msgbox(&text, config::QUEST_LOG_WIDTH, root);
+}
@@ -0,0 +1,128 @@
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::SystemTime;
+
+use crate::structures;
+use crate::myengine;
+use crate::config as config; // change for other game
+
+
+// a config loaded into memory, with the modification time of its file at loading
+struct Entry<T> {
+    value: Arc<T>,
+    modified: Option<SystemTime>,
+}
+
+// all game data read from the config files, loaded once on first use
+struct Resources {
+    monsters: Entry<Vec<structures::MonsterConfig>>,
+    map: Entry<structures::MapConfig>,
+    dialogues: Entry<Vec<structures::DialogueConfig>>,
+    quests: Entry<Vec<structures::QuestConfig>>,
+    difficulty: Entry<Vec<structures::DifficultyConfig>>,
+    items: Entry<Vec<structures::ItemConfig>>,
+}
+
+static RESOURCES: OnceLock<RwLock<Resources>> = OnceLock::new();
+
+// config errors are reported once and the game goes on with the built-in defaults
+static CONFIG_ERRORS: Mutex<Vec<(String, bool)>> = Mutex::new(Vec::new());
+
+fn resources() -> &'static RwLock<Resources> {
+    RESOURCES.get_or_init(|| {
+        RwLock::new(Resources {
+            monsters: load(config::CONFIG_MONSTER_FILE_NAME, myengine::read_valid_monsters, structures::MonsterConfig::defaults),
+            map: load(config::CONFIG_MAP_FILE_NAME, myengine::read_map_config, structures::MapConfig::default),
+            dialogues: load(config::CONFIG_DIALOGUE_FILE_NAME, myengine::read_dialogue_config, Vec::new),
+            quests: load(config::CONFIG_QUEST_FILE_NAME, myengine::read_quest_config, Vec::new),
+            difficulty: load(config::CONFIG_DIFFICULTY_FILE_NAME, myengine::read_difficulty_config, structures::DifficultyConfig::defaults),
+            items: load(config::CONFIG_ITEM_FILE_NAME, myengine::read_item_config, structures::ItemConfig::defaults),
+        })
+    })
+}
+
+fn modified(file_name: &str) -> Option<SystemTime> {
+    std::fs::metadata(myengine::config_file_path(file_name)).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn load<T>(file_name: &str, read: fn() -> Result<T, structures::ConfigError>, fallback: fn() -> T) -> Entry<T> {
+    let modified = modified(file_name);
+    let value = read().unwrap_or_else(|error| {
+        report_config_error(error);
+        fallback()
+    });
+    Entry { value: Arc::new(value), modified }
+}
+
+// reloads the config if its file changed, a config that fails to load keeps its previous value
+fn reload<T>(entry: &mut Entry<T>, file_name: &'static str, read: fn() -> Result<T, structures::ConfigError>) -> Option<(&'static str, Result<(), structures::ConfigError>)> {
+    let modified = modified(file_name);
+    if modified == entry.modified {
+        return None;
+    }
+    entry.modified = modified;
+    let result = read().map(|value| entry.value = Arc::new(value));
+    Some((file_name, result))
+}
+
+// checks the config files for changes, returning the reloaded ones
+pub fn reload_changed() -> Vec<(&'static str, Result<(), structures::ConfigError>)> {
+    let mut resources = resources().write().unwrap();
+    let resources = &mut *resources;
+    vec![
+        reload(&mut resources.monsters, config::CONFIG_MONSTER_FILE_NAME, myengine::read_valid_monsters),
+        reload(&mut resources.map, config::CONFIG_MAP_FILE_NAME, myengine::read_map_config),
+        reload(&mut resources.dialogues, config::CONFIG_DIALOGUE_FILE_NAME, myengine::read_dialogue_config),
+        reload(&mut resources.quests, config::CONFIG_QUEST_FILE_NAME, myengine::read_quest_config),
+        reload(&mut resources.difficulty, config::CONFIG_DIFFICULTY_FILE_NAME, myengine::read_difficulty_config),
+        reload(&mut resources.items, config::CONFIG_ITEM_FILE_NAME, myengine::read_item_config),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+pub fn monsters() -> Arc<Vec<structures::MonsterConfig>> {
+    resources().read().unwrap().monsters.value.clone()
+}
+
+pub fn map() -> Arc<structures::MapConfig> {
+    resources().read().unwrap().map.value.clone()
+}
+
+pub fn dialogues() -> Arc<Vec<structures::DialogueConfig>> {
+    resources().read().unwrap().dialogues.value.clone()
+}
+
+pub fn quests() -> Arc<Vec<structures::QuestConfig>> {
+    resources().read().unwrap().quests.value.clone()
+}
+
+pub fn difficulty() -> Arc<Vec<structures::DifficultyConfig>> {
+    resources().read().unwrap().difficulty.value.clone()
+}
+
+pub fn items() -> Arc<Vec<structures::ItemConfig>> {
+    resources().read().unwrap().items.value.clone()
+}
+
+pub fn report_config_error(error: structures::ConfigError) {
+    let text = error.to_string();
+    let mut errors = CONFIG_ERRORS.lock().unwrap();
+    if !errors.iter().any(|(reported, _)| *reported == text) {
+        eprintln!("{}", text);
+        errors.push((text, false));
+    }
+}
+
+// errors that were not shown to the player yet
+pub fn take_config_errors() -> Vec<String> {
+    let mut errors = CONFIG_ERRORS.lock().unwrap();
+    errors
+        .iter_mut()
+        .filter(|(_, shown)| !*shown)
+        .map(|(text, shown)| {
+            *shown = true;
+            text.clone()
+        })
+        .collect()
+}
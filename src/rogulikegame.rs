@@ -1,75 +1,123 @@
 
-use rand::Rng;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
 use tcod::colors::*;
 use tcod::console::*;
+use tcod::input::{self as tcod_input, Event};
 
 use crate::config; 
 use crate::structures;
 use crate::myengine;
+use crate::paths;
+use crate::resources;
+use crate::quests;
+use crate::input;
+use crate::bosses;
 
 
-fn handle_keys(tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &mut Vec<structures::Object>) -> structures::PlayerAction {
-    use tcod::input::Key;
-    use tcod::input::KeyCode::*;
-    use structures::PlayerAction::*;
+// `player_ready` is false while the player has to wait for the next real-time tick
+fn handle_keys(tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &mut Vec<structures::Object>, player_ready: bool) -> structures::PlayerAction {
+    use input::Command::*;
+    use structures::PlayerAction::{TookTurn, DidnotTakeTurn};
 
-    let key = tcod.root.wait_for_keypress(true);
+    let key = tcod.key;
     let player_alive = objects[config::PLAYER].alive;
+    let command = match game.bindings.command(key) {
+        Some(command) => command,
+        None => return DidnotTakeTurn,
+    };
+    if !player_ready && command.takes_turn() {
+        return DidnotTakeTurn;
+    }
+
+    match (command, player_alive) {
+
+        (Exit, _) => structures::PlayerAction::Exit,
+
+        (Rebind, _) => {
+            if let Err(error) = input::rebinding_screen(&mut game.bindings, &mut tcod.root) {
+                game.messages.add(tr!("bindings.save_failed", error = error), RED);
+            }
+            DidnotTakeTurn
+        }
 
-    match (key, key.text(), player_alive) {
+        (MessageLog, _) => {
+            myengine::message_log(&game.messages, &mut tcod.root);
+            DidnotTakeTurn
+        }
 
-        (Key { code: Escape, .. }, _, _) => Exit,
+        (QuestLog, _) => {
+            quests::quest_log(game, &mut tcod.root);
+            DidnotTakeTurn
+        }
 
-        (Key { code: Number5, .. }, _, true) => {
-            let player_on_stairs = objects.iter().any(|object| object.loc() == objects[config::PLAYER].loc() && object.name == "door");
+        (Descend, true) => {
+            let player_on_stairs = objects.iter().any(|object| object.loc() == objects[config::PLAYER].loc() && object.is_door);
             if player_on_stairs {
-                myengine::next_level(tcod, game, objects);
+                match bosses::door_guard(objects) {
+                    Some(boss) => game.messages.add(tr!("boss.door_guarded", name = boss.name), LIGHT_MAGENTA),
+                    None => myengine::next_level(tcod, game, objects),
+                }
             }
             DidnotTakeTurn
         }
 
-        (Key { code: Number4, .. }, _, true) => {
+        (CharacterSheet, true) => {
             // show character information
             let player = &objects[config::PLAYER];
             let level = player.level;
             let level_up_xp = config::LEVEL_UP_XP_BASE + player.level * config::LEVEL_UP_XP_PER_LEVEL;
             if let Some(attackable) = player.attackable.as_ref() {
-                let msg = format!(
-                    "Character information
-        
-    Level: {}
-    XP: {}
-    XP to lvl up: {}
-        
-    Maximum HP: {}
-    Attack: {}
-    Defense: {}",
-                    level, attackable.xp, level_up_xp - attackable.xp, attackable.max_hp, attackable.damage, attackable.armor
+                let msg = tr!(
+                    "character.sheet",
+                    level = level,
+                    xp = attackable.xp,
+                    xp_to_level = level_up_xp - attackable.xp,
+                    max_hp = attackable.max_hp,
+                    damage = attackable.damage,
+                    armor = attackable.armor,
                 );
                 myengine::msgbox(&msg, config::CHARACTER_SCREEN_WIDTH, &mut tcod.root);
             }
         
             DidnotTakeTurn
         }
-        
-        (Key { code: Up, .. }, _, true) => {
-            myengine::player_move_or_attack(0, -1, game, objects);
-            TookTurn
-        },
-        (Key { code: Down, .. }, _, true) => {
-            myengine::player_move_or_attack(0, 1, game, objects);
-            TookTurn
-        },
-        (Key { code: Left, .. }, _, true) => {
-            myengine::player_move_or_attack(-1, 0, game, objects);
+
+        (MoveUp, true) | (MoveDown, true) | (MoveLeft, true) | (MoveRight, true) |
+        (MoveUpLeft, true) | (MoveUpRight, true) | (MoveDownLeft, true) | (MoveDownRight, true) => {
+            let (dx, dy) = command.direction().unwrap();
+            myengine::player_move_or_attack(dx, dy, tcod, game, objects);
             TookTurn
-        },
-        (Key { code: Right, .. }, _, true) => {
-            myengine::player_move_or_attack(1, 0, game, objects);
+        }
+
+        (Wait, true) => {
+            myengine::player_wait(objects);
             TookTurn
-        },
+        }
+
+        (AutoExplore, true) => {
+            let hp = objects[config::PLAYER].attackable.map_or(0, |f| f.hp);
+            game.auto_action = Some(structures::AutoAction::Explore { hp });
+            DidnotTakeTurn
+        }
+
+        (RestUntilHealed, true) => {
+            myengine::start_rest(true, tcod, game, objects);
+            DidnotTakeTurn
+        }
+
+        (RestUntilDisturbed, true) => {
+            myengine::start_rest(false, tcod, game, objects);
+            DidnotTakeTurn
+        }
+
+        (Look, true) => {
+            myengine::look(tcod, game, objects);
+            DidnotTakeTurn
+        }
         
-        (Key { code: Number1, .. }, _, true) => {
+        (PickUp, true) => {
             //println!("Tried to pick up");
             let item_id = objects
                 .iter()
@@ -80,10 +128,10 @@ fn handle_keys(tcod: &mut structures::Tcod, game: &mut structures::Game, objects
             DidnotTakeTurn
         },
 
-        (Key { code: Number2, .. }, _, true) => {
+        (Inventory, true) => {
             let inventory_index = myengine::inventory_menu(
                 &game.inventory,
-                "Press the key to use an item or any other to cancel.\n",
+                &tr!("inventory.use_header"),
                 &mut tcod.root,
             );
             if let Some(inventory_index) = inventory_index {
@@ -92,17 +140,126 @@ fn handle_keys(tcod: &mut structures::Tcod, game: &mut structures::Game, objects
             DidnotTakeTurn
         }
 
+        (Drop, true) => {
+            let inventory_index = myengine::inventory_menu(
+                &game.inventory,
+                &tr!("inventory.drop_header"),
+                &mut tcod.root,
+            );
+            if let Some(inventory_index) = inventory_index {
+                myengine::drop_item(inventory_index, game, objects);
+                return TookTurn;
+            }
+            DidnotTakeTurn
+        }
+
+        (Fire, true) => {
+            let inventory_index = myengine::inventory_menu(
+                &game.inventory,
+                &tr!("inventory.throw_header"),
+                &mut tcod.root,
+            );
+            if let Some(inventory_index) = inventory_index {
+                if let structures::UseResult::UsedUp = myengine::throw_item(inventory_index, tcod, game, objects) {
+                    return TookTurn;
+                }
+            }
+            DidnotTakeTurn
+        }
+
         _ => DidnotTakeTurn,
     }
     
 }
     
+// performs one step of the current auto action, stopping it when it is finished or interrupted
+fn auto_turn(tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &mut Vec<structures::Object>) -> structures::PlayerAction {
+    use structures::PlayerAction::*;
+
+    let action = match game.auto_action.take() {
+        Some(action) => action,
+        None => return DidnotTakeTurn,
+    };
+    let (hp, max_hp) = objects[config::PLAYER].attackable.map_or((0, 0), |f| (f.hp, f.max_hp));
+
+    if myengine::hostile_in_view(tcod, objects) {
+        game.messages.add(tr!("auto.enemy_in_view"), RED);
+        return DidnotTakeTurn;
+    }
+
+    match action {
+        structures::AutoAction::Travel { x, y } => {
+            let from = objects[config::PLAYER].loc();
+            match myengine::path_step(from, (x, y), game, objects) {
+                Some((step_x, step_y)) => {
+                    myengine::move_by(config::PLAYER, step_x - from.0, step_y - from.1, &game.map, objects);
+                    if objects[config::PLAYER].loc() != (x, y) {
+                        game.auto_action = Some(structures::AutoAction::Travel { x, y });
+                    }
+                    TookTurn
+                }
+                None => {
+                    game.messages.add(tr!("travel.no_path"), RED);
+                    DidnotTakeTurn
+                }
+            }
+        }
+        structures::AutoAction::Explore { hp: last_hp } => {
+            if hp < last_hp {
+                game.messages.add(tr!("explore.hurt"), RED);
+                return DidnotTakeTurn;
+            }
+            if config::AUTO_EXPLORE_PICK_UP {
+                let item_id = objects
+                    .iter()
+                    .position(|object| object.loc() == objects[config::PLAYER].loc() && object.item.is_some());
+                if let Some(item_id) = item_id {
+                    myengine::pick_item_up(item_id, game, objects);
+                }
+            }
+            match myengine::explore_step(game, objects) {
+                Some((step_x, step_y)) => {
+                    let (x, y) = objects[config::PLAYER].loc();
+                    myengine::move_by(config::PLAYER, step_x - x, step_y - y, &game.map, objects);
+                    game.auto_action = Some(structures::AutoAction::Explore { hp });
+                    TookTurn
+                }
+                None => {
+                    game.messages.add(tr!("explore.done"), WHITE);
+                    DidnotTakeTurn
+                }
+            }
+        }
+        structures::AutoAction::Rest { until_healed, hp: last_hp, turns } => {
+            if hp < last_hp {
+                game.messages.add(tr!("rest.hurt"), RED);
+                return DidnotTakeTurn;
+            }
+            if until_healed && hp >= max_hp {
+                game.messages.add(tr!("rest.done"), WHITE);
+                return DidnotTakeTurn;
+            }
+            if turns >= config::REST_MAX_TURNS {
+                game.messages.add(tr!("rest.stop"), WHITE);
+                return DidnotTakeTurn;
+            }
+            myengine::player_wait(objects);
+            let hp = objects[config::PLAYER].attackable.map_or(0, |f| f.hp);
+            game.auto_action = Some(structures::AutoAction::Rest { until_healed, hp, turns: turns + 1 });
+            TookTurn
+        }
+    }
+}
+
 fn ai_turn(id: usize, tcod: &structures::Tcod, game: &mut structures::Game, objects: &mut [structures::Object]) {
     use structures::Ai::*;
     if let Some(ai) = objects[id].ai.take() {
         let new_ai = match ai {
             Basic => ai_basic(id, tcod, game, objects),
             Blind {prev_ai, num_turns} => ai_blind(id, tcod, game, objects, prev_ai, num_turns),
+            Wanderer => ai_wanderer(id, tcod, game, objects),
+            Coward => ai_coward(id, tcod, game, objects),
+            Guard => ai_guard(id, tcod, game, objects),
         };
         objects[id].ai = Some(new_ai);
     }
@@ -110,7 +267,7 @@ fn ai_turn(id: usize, tcod: &structures::Tcod, game: &mut structures::Game, obje
 
 fn ai_basic(id: usize, tcod: &structures::Tcod, game: &mut structures::Game, objects: &mut [structures::Object]) -> structures::Ai {
     let (monster_x, monster_y) = objects[id].loc();
-    if tcod.fov.is_in_fov(monster_x, monster_y) {
+    if objects[id].disposition == structures::Disposition::Hostile && tcod.fov.is_in_fov(monster_x, monster_y) {
         if objects[id].get_distance_to(&objects[config::PLAYER]) >= 2.0 {
             let (player_x, player_y) = objects[config::PLAYER].loc();
             myengine::monster_move(id, player_x, player_y, &game.map, objects);
@@ -122,12 +279,57 @@ fn ai_basic(id: usize, tcod: &structures::Tcod, game: &mut structures::Game, obj
     structures::Ai::Basic
 }
 
+fn ai_wanderer(id: usize, tcod: &structures::Tcod, game: &mut structures::Game, objects: &mut [structures::Object]) -> structures::Ai {
+    let (monster_x, monster_y) = objects[id].loc();
+    if objects[id].disposition == structures::Disposition::Hostile && tcod.fov.is_in_fov(monster_x, monster_y) {
+        ai_basic(id, tcod, game, objects);
+    } else {
+        myengine::move_by(id, myengine::gen_range(-1..2), myengine::gen_range(-1..2), &game.map, objects);
+    }
+    structures::Ai::Wanderer
+}
+
+fn ai_coward(id: usize, tcod: &structures::Tcod, game: &mut structures::Game, objects: &mut [structures::Object]) -> structures::Ai {
+    let (monster_x, monster_y) = objects[id].loc();
+    let wounded = objects[id].attackable.is_some_and(|f| (f.hp as f32) < f.max_hp as f32 * config::COWARD_HP_FRACTION);
+    if wounded && tcod.fov.is_in_fov(monster_x, monster_y) {
+        // stepping towards the point mirrored through the monster leads away from the player
+        let (player_x, player_y) = objects[config::PLAYER].loc();
+        myengine::monster_move(id, 2 * monster_x - player_x, 2 * monster_y - player_y, &game.map, objects);
+    } else {
+        ai_basic(id, tcod, game, objects);
+    }
+    structures::Ai::Coward
+}
+
+fn ai_guard(id: usize, _tcod: &structures::Tcod, game: &mut structures::Game, objects: &mut [structures::Object]) -> structures::Ai {
+    let next_to_player = objects[id].get_distance_to(&objects[config::PLAYER]) < 2.0;
+    if objects[id].disposition == structures::Disposition::Hostile && next_to_player && objects[config::PLAYER].attackable.is_some_and(|f| f.hp > 0) {
+        let (monster, player) = myengine::mut_two(id, config::PLAYER, objects);
+        monster.attack(player, game);
+    }
+    structures::Ai::Guard
+}
+
+// a monster acts once for every full action of energy its speed gathered, slow monsters skip turns
+fn monster_turns(id: usize, tcod: &structures::Tcod, game: &mut structures::Game, objects: &mut [structures::Object]) {
+    objects[id].energy += objects[id].speed;
+    while objects[id].energy >= config::ACTION_ENERGY && objects[id].ai.is_some() && objects[config::PLAYER].alive {
+        objects[id].energy -= config::ACTION_ENERGY;
+        // bosses only use their abilities once they have seen the player
+        if tcod.fov.is_in_fov(objects[id].x, objects[id].y) {
+            bosses::use_abilities(id, game, objects);
+        }
+        ai_turn(id, tcod, game, objects);
+    }
+}
+
 fn ai_blind(id: usize, _tcod: &structures::Tcod, game: &mut structures::Game, objects: &mut [structures::Object], previous_ai: Box<structures::Ai>, num_turns: i32) -> structures::Ai {
     if num_turns >= 0 {
-        myengine::move_by( id, rand::thread_rng().gen_range(-1..2), rand::thread_rng().gen_range(-1..2), &game.map, objects);
+        myengine::move_by( id, myengine::gen_range(-1..2), myengine::gen_range(-1..2), &game.map, objects);
         structures::Ai::Blind{prev_ai: previous_ai, num_turns: num_turns - 1}
     } else {
-        game.messages.add(format!("The {} is no longer confused!", objects[id].name), RED);
+        game.messages.add_category(structures::MessageCategory::Combat, tr!("combat.blind_over", name = objects[id].name), RED);
         *previous_ai
     }
 }
@@ -138,15 +340,15 @@ fn level_up(tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &
 
     if player.attackable.as_ref().map_or(0, |f| f.xp) >= level_up_xp {
         player.level += 1;
-        game.messages.add(format!("You reached level {}!", player.level), YELLOW);
+        game.messages.add_category(structures::MessageCategory::Level, tr!("level.reached", level = player.level), YELLOW);
         let attackable = player.attackable.as_mut().unwrap();
     let mut choice = None;
     while choice.is_none() {
-        choice = myengine::menu("Level up! Choose a stat to raise:\n",
+        choice = myengine::menu(&tr!("level.up_header"),
             &[
-                format!("(+{} HP, from {})", config::PLAYER_MAX_HP_PER_LEVEL, attackable.max_hp),
-                format!("(+{} attack, from {})", config::PLAYER_DAMAGE_PER_LEVEL, attackable.damage),
-                format!("(+{} defense, from {})", config::PLAYER_ARMOR_PER_LEVEL, attackable.armor),
+                tr!("level.up_hp", amount = config::PLAYER_MAX_HP_PER_LEVEL, value = attackable.max_hp),
+                tr!("level.up_attack", amount = config::PLAYER_DAMAGE_PER_LEVEL, value = attackable.damage),
+                tr!("level.up_defense", amount = config::PLAYER_ARMOR_PER_LEVEL, value = attackable.armor),
             ],
             config::LEVEL_SCREEN_WIDTH,
             &mut tcod.root,
@@ -169,53 +371,245 @@ fn level_up(tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &
     }
 }
 
-pub fn play() {
-    tcod::system::set_fps(config::LIMIT_FPS);
-
-    let game_name = "Roguelike Game Prototype";
-    let root = myengine::set_root(game_name);
+// shows the title screen, or goes straight into the game when a save file is given
+pub fn run(load: Option<PathBuf>) -> Result<(), String> {
+    let root = myengine::set_root(config::GAME_NAME)?;
     let mut tcod = myengine::set_tcod(root);
+    myengine::choose_language(&mut tcod.root);
+
+    if let Some(path) = load {
+        match myengine::load_game(&path) {
+            Some((game, objects)) => {
+                myengine::initialise_fov(&mut tcod, &game.map);
+                play(&mut tcod, game, objects);
+            }
+            None => myengine::msgbox(&tr!("title.load_failed", path = path.display()), config::MAIN_MENU_WIDTH, &mut tcod.root),
+        }
+    }
+
+    while !tcod.root.window_closed() {
+        match main_menu(&mut tcod.root) {
+            Some(0) => {
+                if let Some(mode) = choose_mode(&mut tcod.root) {
+                    let (game, objects) = new_game(mode, &mut tcod);
+                    play(&mut tcod, game, objects);
+                }
+            }
+            Some(1) => match myengine::load_game(&paths::user(config::SAVE_FILE_NAME)) {
+                Some((game, objects)) => {
+                    myengine::initialise_fov(&mut tcod, &game.map);
+                    play(&mut tcod, game, objects);
+                }
+                None => myengine::msgbox(&tr!("title.no_save"), config::MAIN_MENU_WIDTH, &mut tcod.root),
+            },
+            Some(2) => run_editor("edit-monsters", &mut tcod.root),
+            Some(3) => run_editor("edit-map", &mut tcod.root),
+            Some(4) => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn main_menu(root: &mut Root) -> Option<usize> {
+    root.set_default_foreground(LIGHT_YELLOW);
+    root.clear();
+    root.print_ex(config::SCREEN_WIDTH / 2, config::SCREEN_HEIGHT / 4, BackgroundFlag::None, TextAlignment::Center, config::GAME_NAME);
+    root.print_ex(config::SCREEN_WIDTH / 2, config::SCREEN_HEIGHT - 2, BackgroundFlag::None, TextAlignment::Center, tr!("title.footer"));
+
+    let options = [tr!("title.new_game"), tr!("title.continue"), tr!("title.monster_editor"), tr!("title.map_editor"), tr!("title.quit")];
+    myengine::menu("", &options, config::MAIN_MENU_WIDTH, root)
+}
+
+fn choose_mode(root: &mut Root) -> Option<structures::GameMode> {
+    let options = [tr!("title.turn_based"), tr!("title.real_time")];
+    match myengine::menu(&tr!("title.choose_mode"), &options, config::MAIN_MENU_WIDTH, root) {
+        Some(0) => Some(structures::GameMode::TurnBased),
+        Some(1) => Some(structures::GameMode::RealTime),
+        _ => None,
+    }
+}
+
+// the editors open druid and tcod windows of their own, so they run as a separate process
+fn run_editor(command: &str, root: &mut Root) {
+    let status = std::env::current_exe().and_then(|exe| {
+        let mut editor = std::process::Command::new(exe);
+        editor.arg(command);
+        if let Some(dir) = paths::data_dir() {
+            editor.arg("--data-dir").arg(dir);
+        }
+        if let Some(dir) = paths::config_dir() {
+            editor.arg("--config-dir").arg(dir);
+        }
+        editor.status()
+    });
+    if let Err(error) = status {
+        myengine::msgbox(&tr!("title.editor_failed", error = error), config::MAIN_MENU_WIDTH, root);
+    }
+}
+
+fn new_game(mode: structures::GameMode, tcod: &mut structures::Tcod) -> (structures::Game, Vec<structures::Object>) {
+    myengine::delete_save();
 
     let player = myengine::create_player();
     
     let mut objects = vec![player];
 
     let mut game = structures::Game {
-        map: myengine::generate_map(&mut objects),
+        map: myengine::generate_map(&mut objects, 1),
         messages: structures::Messages::new(),
         inventory: vec![],
         level: 1,
+        gold: 0,
+        dropped_items: vec![],
+        summoned: vec![],
+        bosses: vec![],
+        quests: quests::starting_quests(),
+        bindings: input::load_bindings(),
+        auto_action: None,
+        mode,
+        animations: vec![],
+        stats: Default::default(),
     };
-    for y in 0..config::MAP_HEIGHT {
-        for x in 0..config::MAP_WIDTH {
-            tcod.fov.set(x, y, !game.map[x as usize][y as usize].is_visible, !game.map[x as usize][y as usize].collision_enabled);
+    myengine::initialise_fov(tcod, &game.map);
+
+    game.messages.add(tr!("game.welcome"), RED);
+    bosses::enter_level(tcod, &mut game, &mut objects);
+
+    (game, objects)
+}
+
+fn game_over(tcod: &mut structures::Tcod, game: &structures::Game, objects: &[structures::Object]) {
+    let quests_completed = game.quests.iter().filter(|quest| quest.state != structures::QuestState::Active).count();
+    let summary = tr!(
+        "game_over.summary",
+        depth = game.level,
+        level = objects[config::PLAYER].level,
+        turns = game.stats.turns,
+        kills = game.stats.kills,
+        gold = game.gold,
+        quests = quests_completed,
+    );
+    myengine::msgbox(&summary, config::GAME_OVER_SCREEN_WIDTH, &mut tcod.root);
+}
+
+// picks up config files changed while playing, the new palette is used by the next frame
+fn hot_reload(game: &mut structures::Game, objects: &mut [structures::Object]) {
+    for (file_name, result) in resources::reload_changed() {
+        match result {
+            Ok(()) => {
+                game.messages.add(tr!("config.reloaded", file = file_name), LIGHT_BLUE);
+                if file_name == config::CONFIG_MONSTER_FILE_NAME || file_name == config::CONFIG_DIFFICULTY_FILE_NAME {
+                    myengine::update_monsters(objects, game.level);
+                }
+            }
+            Err(error) => game.messages.add(tr!("config.reload_failed", error = error), RED),
         }
     }
+}
 
-    game.messages.add("Welcome to Dungeon. Prepare for danger!", RED);
-
+// runs the game until the player dies or leaves, leaving saves the game
+fn play(tcod: &mut structures::Tcod, mut game: structures::Game, mut objects: Vec<structures::Object>) {
     let mut previous_player_position = (-1, -1);
+    let mut last_monster_tick = Instant::now();
+    let mut last_player_turn: Option<Instant> = None;
+    let mut last_reload_check = Instant::now();
 
     while !tcod.root.window_closed() {
+
+        for error in resources::take_config_errors() {
+            game.messages.add(tr!("config.error", error = error), RED);
+        }
+        if config::HOT_RELOAD && last_reload_check.elapsed() >= Duration::from_millis(config::HOT_RELOAD_CHECK_MS) {
+            last_reload_check = Instant::now();
+            hot_reload(&mut game, &mut objects);
+        }
         
         tcod.screen.clear();
 
         let fov_recompute = previous_player_position != (objects[config::PLAYER].x, objects[config::PLAYER].y);
-        myengine::render(&mut tcod, &mut game, &objects, fov_recompute);
+        myengine::render(tcod, &mut game, &objects, fov_recompute);
         tcod.root.flush();
-        level_up(&mut tcod, &mut game, &mut objects);
+        level_up(tcod, &mut game, &mut objects);
         previous_player_position = objects[config::PLAYER].loc();
-        let player_action = handle_keys(&mut tcod, &mut game, &mut objects);
+
+        tcod.key = Default::default();
+        // mouse motion can queue many events, so the queue is read up to the next key press or click every frame
+        while let Some((_, event)) = tcod_input::check_for_event(tcod_input::MOUSE | tcod_input::KEY_PRESS) {
+            match event {
+                Event::Mouse(mouse) => {
+                    tcod.mouse = mouse;
+                    if mouse.lbutton_pressed || mouse.rbutton_pressed {
+                        break;
+                    }
+                }
+                Event::Key(key) => {
+                    tcod.key = key;
+                    break;
+                }
+            }
+        }
+
+        // in real time the player acts at most once per tick, like the monsters
+        let player_ready = match game.mode {
+            structures::GameMode::TurnBased => true,
+            structures::GameMode::RealTime => last_player_turn.is_none_or(|turn| turn.elapsed() >= Duration::from_millis(config::MONSTER_TICK_MS)),
+        };
+        let player_action = if game.auto_action.is_some() {
+            // any key press interrupts the auto action
+            if tcod.key.code != tcod_input::KeyCode::NoKey {
+                myengine::flush_input();
+                game.auto_action = None;
+                game.messages.add(tr!("auto.interrupted"), WHITE);
+                structures::PlayerAction::DidnotTakeTurn
+            } else if player_ready {
+                auto_turn(tcod, &mut game, &mut objects)
+            } else {
+                structures::PlayerAction::DidnotTakeTurn
+            }
+        } else if tcod.mouse.lbutton_pressed && objects[config::PLAYER].alive {
+            tcod.mouse.lbutton_pressed = false;
+            myengine::start_travel(tcod.mouse.cx as i32, tcod.mouse.cy as i32, &mut game, &objects);
+            structures::PlayerAction::DidnotTakeTurn
+        } else {
+            handle_keys(tcod, &mut game, &mut objects, player_ready)
+        };
         if player_action == structures::PlayerAction::Exit {
             break;
         }
-        if objects[config::PLAYER].alive && player_action != structures::PlayerAction::DidnotTakeTurn {
+        if player_action == structures::PlayerAction::TookTurn {
+            game.stats.turns += 1;
+            last_player_turn = Some(Instant::now());
+        }
+        let monsters_turn = match game.mode {
+            structures::GameMode::TurnBased => player_action != structures::PlayerAction::DidnotTakeTurn,
+            structures::GameMode::RealTime => last_monster_tick.elapsed() >= Duration::from_millis(config::MONSTER_TICK_MS),
+        };
+        if objects[config::PLAYER].alive && monsters_turn {
+            last_monster_tick = Instant::now();
             for id in 0..objects.len() {
                 if objects[id].ai.is_some() {
-                    ai_turn(id, &tcod, &mut game, &mut objects);
+                    monster_turns(id, tcod, &mut game, &mut objects);
                 }
             }
         }
+        objects.append(&mut game.dropped_items);
+        objects.append(&mut game.summoned);
+        quests::claim_rewards(&mut game, &mut objects);
+
+        if !objects[config::PLAYER].alive {
+            // show the final blow before the summary
+            tcod.screen.clear();
+            myengine::render(tcod, &mut game, &objects, false);
+            tcod.root.flush();
+            myengine::delete_save();
+            game_over(tcod, &game, &objects);
+            return;
+        }
     }
-}
 
+    game.auto_action = None;
+    if let Err(error) = myengine::save_game(&game, &objects) {
+        myengine::msgbox(&tr!("title.save_failed", error = error), config::MAIN_MENU_WIDTH, &mut tcod.root);
+    }
+}
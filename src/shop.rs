@@ -0,0 +1,73 @@
+use tcod::colors::*;
+
+use crate::structures;
+use crate::myengine;
+use crate::resources;
+use crate::config as config; // change for other game
+
+
+pub fn spawn_shopkeeper(room: structures::Rect, map: &structures::Map, objects: &mut Vec<structures::Object>) {
+    let x = myengine::gen_range(room.x1 + 1..room.x2);
+    let y = myengine::gen_range(room.y1 + 1..room.y2);
+    if myengine::is_blocked(x, y, map, objects) {
+        return;
+    }
+
+    let mut shopkeeper = structures::Object::translated(x, y, '@', GOLD, structures::NameKey::new("npc.shopkeeper"), true);
+    shopkeeper.always_visible = true;
+    shopkeeper.disposition = structures::Disposition::Friendly;
+    shopkeeper.dialogue = Some("shopkeeper".into());
+    shopkeeper.shop = Some(structures::Shop {
+        stock: resources::items().iter().filter(|config| config.in_shop).map(|config| config.item).collect(),
+    });
+    objects.push(shopkeeper);
+}
+
+pub fn trade(shop_id: usize, tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &[structures::Object]) {
+    let stock = match &objects[shop_id].shop {
+        Some(shop) => shop.stock.clone(),
+        None => return,
+    };
+
+    let header = tr!("shop.welcome", gold = game.gold);
+    let choice = myengine::menu(&header, &[tr!("shop.buy"), tr!("shop.sell")], config::SHOP_WIDTH, &mut tcod.root);
+    match choice {
+        Some(0) => buy(&stock, tcod, game),
+        Some(1) => sell(tcod, game),
+        _ => {}
+    }
+}
+
+fn buy(stock: &[structures::Item], tcod: &mut structures::Tcod, game: &mut structures::Game) {
+    let options: Vec<String> = stock.iter().map(|item| tr!("shop.price", item = item.name(), price = item.price())).collect();
+    let header = tr!("shop.buy_header", gold = game.gold);
+    let choice = myengine::menu(&header, &options, config::SHOP_WIDTH, &mut tcod.root);
+
+    if let Some(choice) = choice {
+        let item = stock[choice];
+        if game.gold < item.price() {
+            game.messages.add_category(structures::MessageCategory::Loot, tr!("shop.cannot_afford", item = item.name()), RED);
+        } else if myengine::inventory_weight(&game.inventory) + item.weight() > config::MAX_CARRY_WEIGHT {
+            game.messages.add_category(structures::MessageCategory::Loot, tr!("shop.too_heavy", item = item.name()), RED);
+        } else {
+            game.gold -= item.price();
+            game.messages.add_category(structures::MessageCategory::Loot, tr!("shop.bought", item = item.name(), price = item.price()), GOLD);
+            myengine::add_to_inventory(structures::Object::new_item(0, 0, item), game);
+        }
+    }
+}
+
+fn sell(tcod: &mut structures::Tcod, game: &mut structures::Game) {
+    let inventory_index = myengine::inventory_menu(
+        &game.inventory,
+        &tr!("shop.sell_header"),
+        &mut tcod.root,
+    );
+
+    if let Some(inventory_index) = inventory_index {
+        let item = myengine::take_from_inventory(inventory_index, game);
+        let price = item.item.map_or(0, |item| item.price()) / config::SELL_PRICE_DIVIDER;
+        game.gold += price;
+        game.messages.add_category(structures::MessageCategory::Loot, tr!("shop.sold", item = item.name, price = price), GOLD);
+    }
+}
@@ -1,16 +1,19 @@
 use tcod::colors::*;
+use tcod::console::*;
+use tcod::input::{self, Event, KeyCode};
 
 use crate::structures;
+use crate::myengine;
 use crate::config as config; // change for other game
 
 pub fn cast_heal(_inventory_id: usize, _tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &mut [structures::Object]) -> structures::UseResult {
     if let Some(attackable) = objects[config::PLAYER].attackable {
         if attackable.hp == attackable.max_hp {
-            game.messages.add("You don't need a heal potion.", RED);
+            game.messages.add(tr!("skill.heal_not_needed"), RED);
             return structures::UseResult::Cancelled;
         }
 
-        game.messages.add(format!("You was healed by {}!", config::HEAL_AMOUNT.to_string()), LIGHT_YELLOW);
+        game.messages.add(tr!("skill.healed", amount = config::HEAL_AMOUNT), LIGHT_YELLOW);
         objects[config::PLAYER].heal(config::HEAL_AMOUNT);
         return structures::UseResult::UsedUp;
     }
@@ -20,10 +23,10 @@ pub fn cast_heal(_inventory_id: usize, _tcod: &mut structures::Tcod, game: &mut
 pub fn cast_dd(_inventory_id: usize, _tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &mut [structures::Object]) -> structures::UseResult {
     if let Some(attackable) = objects[config::PLAYER].attackable {
         if attackable.armor <= 0 {
-            game.messages.add("You can't reduce your armor", RED);
+            game.messages.add(tr!("skill.armor_too_low"), RED);
             return structures::UseResult::Cancelled;
         }
-        game.messages.add("You have double damage, but armor reduced by half!", LIGHT_BLUE);
+        game.messages.add(tr!("skill.double_damage"), LIGHT_BLUE);
         objects[config::PLAYER].use_double_damage();
         return structures::UseResult::UsedUp;
     }
@@ -33,15 +36,15 @@ pub fn cast_dd(_inventory_id: usize, _tcod: &mut structures::Tcod, game: &mut st
 pub fn cast_fire(_inventory_id: usize, tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &mut [structures::Object]) -> structures::UseResult {
     let monster_id = closest_monster(tcod, objects, config::FIRE_RANGE);
     if let Some(monster_id) = monster_id {
-        game.messages.add(
-            format!("A fire mark burns the {}! The damage is {} hit points.", objects[monster_id].name, config::FIRE_DAMAGE),LIGHT_ORANGE);
+        game.messages.add_category(structures::MessageCategory::Combat,
+            tr!("skill.fire", name = objects[monster_id].name, damage = config::FIRE_DAMAGE),LIGHT_ORANGE);
         if let Some(xp) = objects[monster_id].get_damage(config::FIRE_DAMAGE, game) {
             objects[config::PLAYER].attackable.as_mut().unwrap().xp += xp;
         }
         structures::UseResult::UsedUp
     } else {
         game.messages
-            .add("No enemy is close enough to burn.", RED);
+            .add(tr!("skill.fire_no_target"), RED);
             structures::UseResult::Cancelled
     }
 }
@@ -53,16 +56,16 @@ pub fn cast_blind(_inventory_id: usize, tcod: &mut structures::Tcod, game: &mut
     if let Some(monster_id) = monster_id {
         let old_ai = objects[monster_id].ai.take().unwrap_or(structures::Ai::Basic);
         objects[monster_id].ai = Some(structures::Ai::Blind {prev_ai: Box::new(old_ai), num_turns: config::BLIND_NUM_TURNS});
-        game.messages.add(
-            format!("The eyes of {} look vacant, as he starts to stumble around!", objects[monster_id].name), LIGHT_GREEN);
+        game.messages.add_category(structures::MessageCategory::Combat,
+            tr!("skill.blind", name = objects[monster_id].name), LIGHT_GREEN);
         structures::UseResult::UsedUp
     } else {
-        game.messages.add("No enemy is close enough to strike.", RED);
+        game.messages.add(tr!("skill.blind_no_target"), RED);
         structures::UseResult::Cancelled
     }
 }
 
-fn closest_monster(tcod: &structures::Tcod, objects: &[structures::Object], max_range: i32) -> Option<usize> {
+pub fn closest_monster(tcod: &structures::Tcod, objects: &[structures::Object], max_range: i32) -> Option<usize> {
     let mut closest_enemy = None;
     let mut closest_dist = (max_range + 1) as f32; // start with (slightly more than) maximum range
 
@@ -70,6 +73,7 @@ fn closest_monster(tcod: &structures::Tcod, objects: &[structures::Object], max_
         if (id != config::PLAYER)
             && object.attackable.is_some()
             && object.ai.is_some()
+            && object.disposition == structures::Disposition::Hostile
             && tcod.fov.is_in_fov(object.x, object.y)
         {
             let dist = objects[config::PLAYER].get_distance_to(object);
@@ -81,3 +85,43 @@ fn closest_monster(tcod: &structures::Tcod, objects: &[structures::Object], max_
     }
     closest_enemy
 }
+
+// lets the player click a tile in view and in range, none when cancelled with a right click or escape
+pub fn target_tile(tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &[structures::Object], max_range: i32) -> Option<(i32, i32)> {
+    game.messages.add(tr!("target.prompt"), LIGHT_CYAN);
+    loop {
+        tcod.key = Default::default();
+        match input::check_for_event(input::KEY_PRESS | input::MOUSE) {
+            Some((_, Event::Mouse(mouse))) => tcod.mouse = mouse,
+            Some((_, Event::Key(key))) => tcod.key = key,
+            None => {}
+        }
+        tcod.screen.clear();
+        myengine::render(tcod, game, objects, false);
+        tcod.root.flush();
+
+        let (x, y) = (tcod.mouse.cx as i32, tcod.mouse.cy as i32);
+        let (player_x, player_y) = objects[config::PLAYER].loc();
+        let in_fov = x < config::MAP_WIDTH && y < config::MAP_HEIGHT && tcod.fov.is_in_fov(x, y);
+        let in_range = (x - player_x).pow(2) + (y - player_y).pow(2) <= max_range.pow(2);
+        if tcod.mouse.lbutton_pressed && in_fov && in_range {
+            tcod.mouse.lbutton_pressed = false;
+            return Some((x, y));
+        }
+        if tcod.mouse.rbutton_pressed || tcod.key.code == KeyCode::Escape {
+            tcod.mouse.rbutton_pressed = false;
+            return None;
+        }
+    }
+}
+
+// like `target_tile`, but only accepts a tile with a creature that can be attacked
+pub fn target_monster(tcod: &mut structures::Tcod, game: &mut structures::Game, objects: &[structures::Object], max_range: i32) -> Option<usize> {
+    loop {
+        let (x, y) = target_tile(tcod, game, objects, max_range)?;
+        let target = objects.iter().position(|object| object.loc() == (x, y) && object.attackable.is_some());
+        if let Some(id) = target.filter(|&id| id != config::PLAYER) {
+            return Some(id);
+        }
+    }
+}
@@ -2,10 +2,66 @@ use tcod::colors::*;
 use tcod::console::*;
 
 use tcod::map::{Map as FovMap};
+use tcod::input::{Key, Mouse};
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::path::{Path, PathBuf};
 
 use serde_derive::*;
 
+use crate::config;
+use crate::myengine;
+use crate::quests;
+use crate::input;
+use crate::resources;
+
+// formats a config file can be written in, told apart by the extension
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Ron,
+}
+
+impl ConfigFormat {
+    // the order in which a config is looked up when files in several formats exist
+    pub const ALL: [ConfigFormat; 3] = [ConfigFormat::Json, ConfigFormat::Toml, ConfigFormat::Ron];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "json",
+            ConfigFormat::Toml => "toml",
+            ConfigFormat::Ron => "ron",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+        ConfigFormat::ALL
+            .iter()
+            .copied()
+            .find(|format| extension.eq_ignore_ascii_case(format.extension()))
+            .unwrap_or(ConfigFormat::Json)
+    }
+}
+
+// layout shared by all config files: the format version and the configs themselves
 #[derive(Debug, Deserialize, Serialize)]
+pub struct ConfigFile<T> {
+    // files written before the format was versioned count as version 0
+    #[serde(default)]
+    pub version: u32,
+    pub saved_configs: T,
+}
+
+impl<T> ConfigFile<T> {
+    pub fn new(saved_configs: T) -> Self {
+        ConfigFile { version: config::CONFIG_VERSION, saved_configs }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct MonsterConfig {
     pub symbol: char,
     pub name: String,
@@ -15,14 +71,253 @@ pub struct MonsterConfig {
     pub r: u8,
     pub g: u8,
     pub b: u8,
+    #[serde(default)]
+    pub loot: Vec<LootConfig>,
+    #[serde(default)]
+    pub disposition: Disposition,
+    #[serde(default)]
+    pub dialogue: Option<String>,
+    #[serde(default = "default_monster_xp")]
+    pub xp: i32,
+    #[serde(default)]
+    pub ai: AiKind,
+    // energy gained every turn, a monster acts once for every `config::ACTION_ENERGY` it has
+    #[serde(default = "default_speed")]
+    pub speed: i32,
+    // relative chance to be picked among the monsters allowed at the depth, 0 never spawns
+    #[serde(default = "default_spawn_weight")]
+    pub spawn_weight: u32,
+    #[serde(default = "default_min_depth")]
+    pub min_depth: u32,
+    // no upper bound when missing
+    #[serde(default)]
+    pub max_depth: Option<u32>,
+    #[serde(default = "default_group_size")]
+    pub min_group: u32,
+    #[serde(default = "default_group_size")]
+    pub max_group: u32,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub boss: Option<BossConfig>,
+}
+
+fn default_monster_xp() -> i32 {
+    config::DEFAULT_MONSTER_XP
+}
+
+pub fn default_speed() -> i32 {
+    config::NORMAL_SPEED
+}
+
+fn default_spawn_weight() -> u32 {
+    config::DEFAULT_SPAWN_WEIGHT
+}
+
+fn default_min_depth() -> u32 {
+    1
+}
+
+fn default_group_size() -> u32 {
+    1
+}
+
+impl MonsterConfig {
+    pub fn allowed_at(&self, level: u32) -> bool {
+        self.min_depth <= level && self.max_depth.is_none_or(|max_depth| level <= max_depth)
+    }
+
+    // monsters used when the monster config can not be loaded
+    pub fn defaults() -> Vec<Self> {
+        let monster = |symbol, name: &str, max_hp, damage, armor, color: Color| MonsterConfig {
+            symbol,
+            name: name.into(),
+            max_hp,
+            damage,
+            armor,
+            r: color.r,
+            g: color.g,
+            b: color.b,
+            loot: vec![],
+            disposition: Disposition::Hostile,
+            dialogue: None,
+            xp: config::DEFAULT_MONSTER_XP,
+            ai: AiKind::Basic,
+            speed: config::NORMAL_SPEED,
+            spawn_weight: config::DEFAULT_SPAWN_WEIGHT,
+            min_depth: 1,
+            max_depth: None,
+            min_group: 1,
+            max_group: 1,
+            description: String::new(),
+            boss: None,
+        };
+        vec![
+            monster('o', "Orc", 10, 3, 0, DESATURATED_GREEN),
+            monster('T', "Troll", 16, 4, 1, DARKER_GREEN),
+        ]
+    }
+}
+
+// one row of a monster loot table, rolled when the monster dies
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct LootConfig {
+    pub item: Item,
+    pub chance: f32,
+    pub min_count: i32,
+    pub max_count: i32,
+    // added to the chance for every dungeon level after the first
+    #[serde(default)]
+    pub depth_bonus: f32,
+}
+
+// a unique monster, spawned once per run next to the door of its level instead of at random
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BossConfig {
+    pub depth: u32,
+    #[serde(default)]
+    pub abilities: Vec<BossAbility>,
+    #[serde(default)]
+    pub guaranteed_loot: Vec<GuaranteedLoot>,
+    // shown when the player enters the level, a generic announcement is used when empty
+    #[serde(default)]
+    pub announcement: String,
+}
+
+// tagged inside the table, toml can not write enum variants with fields otherwise
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BossAbility {
+    // heals every action
+    Regenerate { amount: i32 },
+    // calls a monster of the monster config to its side every few actions, at most `max` times
+    Summon { monster: String, every_turns: u32, max: u32 },
+    // hits harder once its HP fall below the fraction
+    Enrage { below_hp_fraction: f32, damage_bonus: i32 },
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct GuaranteedLoot {
+    pub item: Item,
+    pub count: i32,
+}
+
+// what a boss remembers between its actions
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct BossState {
+    pub abilities: Vec<BossAbility>,
+    pub turns: u32,
+    pub summoned: u32,
+    pub enraged: bool,
+}
+
+impl BossState {
+    // damage the boss gained from its abilities on top of its config
+    pub fn damage_bonus(&self) -> i32 {
+        if !self.enraged {
+            return 0;
+        }
+        self.abilities.iter().map(|ability| match ability {
+            BossAbility::Enrage { damage_bonus, .. } => *damage_bonus,
+            _ => 0,
+        }).sum()
+    }
+}
+
+pub type MonsterConfigJson = ConfigFile<Vec<MonsterConfig>>;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DialogueConfig {
+    pub id: String,
+    pub nodes: Vec<DialogueNode>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
-pub struct MonsterConfigJson {
-    pub saved_configs: Vec<MonsterConfig>,
+pub struct DialogueNode {
+    pub text: String,
+    pub options: Vec<DialogueOption>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+pub struct DialogueOption {
+    pub text: String,
+    // index of the next node, the conversation ends when there is none
+    #[serde(default)]
+    pub next: Option<usize>,
+    #[serde(default)]
+    pub action: Option<DialogueAction>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DialogueAction {
+    OpenShop,
+    GiveQuest(String),
+}
+
+pub type DialogueConfigJson = ConfigFile<Vec<DialogueConfig>>;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct QuestConfig {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub objective: Objective,
+    #[serde(default)]
+    pub reward: Reward,
+    // quests which are not started automatically are given by NPCs in dialogue
+    #[serde(default)]
+    pub auto_start: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Objective {
+    Kill { monster: String, count: i32 },
+    ReachLevel { level: u32 },
+    Retrieve { item: Item },
+    TalkTo { npc: String },
+}
+
+impl Objective {
+    pub fn target(&self) -> i32 {
+        use Objective::*;
+        match self {
+            Kill { count, .. } => *count,
+            ReachLevel { level } => *level as i32,
+            Retrieve { .. } => 1,
+            TalkTo { .. } => 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Reward {
+    #[serde(default)]
+    pub xp: i32,
+    #[serde(default)]
+    pub gold: i32,
+    #[serde(default)]
+    pub items: Vec<Item>,
+}
+
+pub type QuestConfigJson = ConfigFile<Vec<QuestConfig>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum QuestState {
+    Active,
+    Completed,
+    Rewarded,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Quest {
+    pub config: QuestConfig,
+    pub progress: i32,
+    pub state: QuestState,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct MapConfig {
     pub light_wall_r: u8,
     pub light_wall_g: u8,
@@ -38,9 +333,172 @@ pub struct MapConfig {
     pub dark_ground_b: u8,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct MapConfigJson {
-    pub saved_configs: MapConfig,
+impl Default for MapConfig {
+    fn default() -> Self {
+        MapConfig {
+            light_wall_r: config::COLOR_LIGHT_WALL.r,
+            light_wall_g: config::COLOR_LIGHT_WALL.g,
+            light_wall_b: config::COLOR_LIGHT_WALL.b,
+            dark_wall_r: config::COLOR_DARK_WALL.r,
+            dark_wall_g: config::COLOR_DARK_WALL.g,
+            dark_wall_b: config::COLOR_DARK_WALL.b,
+            light_ground_r: config::COLOR_LIGHT_GROUND.r,
+            light_ground_g: config::COLOR_LIGHT_GROUND.g,
+            light_ground_b: config::COLOR_LIGHT_GROUND.b,
+            dark_ground_r: config::COLOR_DARK_GROUND.r,
+            dark_ground_g: config::COLOR_DARK_GROUND.g,
+            dark_ground_b: config::COLOR_DARK_GROUND.b,
+        }
+    }
+}
+
+pub type MapConfigJson = ConfigFile<MapConfig>;
+
+// one row of the difficulty table, used from `from_level` until the level of the next row
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DifficultyConfig {
+    pub from_level: u32,
+    pub max_room_monsters: i32,
+    pub max_room_items: i32,
+    #[serde(default = "default_multiplier")]
+    pub hp_multiplier: f32,
+    #[serde(default = "default_multiplier")]
+    pub damage_multiplier: f32,
+    #[serde(default = "default_multiplier")]
+    pub armor_multiplier: f32,
+    #[serde(default = "default_multiplier")]
+    pub xp_multiplier: f32,
+    // spawn weights by monster name, replacing the weights of the monster config
+    #[serde(default)]
+    pub spawn_weights: HashMap<String, u32>,
+}
+
+fn default_multiplier() -> f32 {
+    1.0
+}
+
+impl DifficultyConfig {
+    // difficulty used when the difficulty config can not be loaded
+    pub fn defaults() -> Vec<Self> {
+        vec![DifficultyConfig {
+            from_level: 1,
+            max_room_monsters: config::MAX_ROOM_MONSTERS,
+            max_room_items: config::MAX_ROOM_ITEMS,
+            hp_multiplier: 1.0,
+            damage_multiplier: 1.0,
+            armor_multiplier: 1.0,
+            xp_multiplier: 1.0,
+            spawn_weights: HashMap::new(),
+        }]
+    }
+
+    // the row for the dungeon level, the table is sorted by `from_level` and starts at level 1
+    pub fn for_level(table: &[DifficultyConfig], level: u32) -> Option<&DifficultyConfig> {
+        table.iter().rev().find(|row| row.from_level <= level).or_else(|| table.first())
+    }
+
+    pub fn spawn_weight(&self, monster: &MonsterConfig) -> u32 {
+        self.spawn_weights.get(&monster.name).copied().unwrap_or(monster.spawn_weight)
+    }
+
+    pub fn scale(value: i32, multiplier: f32) -> i32 {
+        (value as f32 * multiplier).round() as i32
+    }
+}
+
+pub type DifficultyConfigJson = ConfigFile<Vec<DifficultyConfig>>;
+
+// the price of an item and whether shopkeepers sell it
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ItemConfig {
+    pub item: Item,
+    pub price: i32,
+    #[serde(default)]
+    pub in_shop: bool,
+}
+
+impl ItemConfig {
+    // prices used when the item config can not be loaded
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            ItemConfig { item: Item::Heal, price: 20, in_shop: true },
+            ItemConfig { item: Item::Fire, price: 35, in_shop: true },
+            ItemConfig { item: Item::DoubleDamage, price: 50, in_shop: true },
+            ItemConfig { item: Item::Blind, price: 25, in_shop: true },
+            ItemConfig { item: Item::Gold, price: 1, in_shop: false },
+        ]
+    }
+}
+
+pub type ItemConfigJson = ConfigFile<Vec<ItemConfig>>;
+
+
+// everything that can go wrong while loading or saving a config
+#[derive(Debug)]
+pub enum ConfigError {
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        message: String,
+    },
+    NoMonsters(PathBuf),
+    UnsupportedVersion {
+        path: PathBuf,
+        version: u32,
+    },
+    Invalid {
+        path: PathBuf,
+        errors: Vec<ValidationError>,
+    },
+    EmptyField(&'static str),
+    InvalidNumber {
+        field: &'static str,
+        value: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ConfigError::*;
+        match self {
+            Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            Parse { path, message } => write!(f, "{} is not a valid config: {}", path.display(), message),
+            NoMonsters(path) => write!(f, "{} does not contain any monsters", path.display()),
+            UnsupportedVersion { path, version } => write!(f, "{} has format version {}, this game reads up to version {}", path.display(), version, config::CONFIG_VERSION),
+            Invalid { path, errors } => {
+                let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                write!(f, "{}: {}", path.display(), errors.join("; "))
+            }
+            EmptyField(field) => write!(f, "{} can not be empty", field),
+            InvalidNumber { field, value } => write!(f, "{} must be a number in range, got '{}'", field, value),
+        }
+    }
+}
+
+// a problem with one entry of a config which was loaded fine otherwise
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationError {
+    // which entry is wrong, like "monster 2 'Orc'"
+    pub entry: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.entry, self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
 }
 
 
@@ -49,11 +507,13 @@ pub struct Tcod {
     pub screen: Offscreen,
     pub fov: FovMap,
     pub panel: Offscreen,
+    pub key: Key,
+    pub mouse: Mouse,
 }
 
 
 // all map is only tiles
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub struct Tile {
     pub collision_enabled: bool,
     pub is_visible: bool,
@@ -111,7 +571,7 @@ impl Rect{
 }
 
 
-#[derive(Debug)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Object {
     pub x: i32,
     pub y: i32,
@@ -125,14 +585,75 @@ pub struct Object {
     pub item: Option<Item>,
     pub always_visible: bool,
     pub level: i32,
+    pub count: i32,
+    pub loot: Vec<LootConfig>,
+    pub shop: Option<Shop>,
+    #[serde(default)]
+    pub disposition: Disposition,
+    pub dialogue: Option<String>,
+    #[serde(default = "default_speed")]
+    pub speed: i32,
+    #[serde(default)]
+    pub energy: i32,
+    #[serde(default)]
+    pub boss: Option<BossState>,
+    // the name is translated, so the door is recognised by this flag
+    #[serde(default)]
+    pub is_door: bool,
+    // saves keep the key of a translated name, the name is translated again when the save is loaded
+    #[serde(default)]
+    pub name_key: Option<NameKey>,
+}
+
+// the translation key of a name, with the `{name}` parameter of the text if it has one
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NameKey {
+    pub key: String,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+impl NameKey {
+    pub fn new(key: &str) -> Self {
+        NameKey { key: key.into(), name: None }
+    }
+
+    pub fn text(&self) -> String {
+        match &self.name {
+            Some(name) => tr!(&self.key, name = name),
+            None => tr!(&self.key),
+        }
+    }
 }
 
 impl Object {
     pub fn new(x: i32, y: i32, symbol: char, color: Color, name: &str, blocks: bool) -> Self {
-        Object{x, y, symbol, color, name: name.into(), blocks, alive: false, attackable: None, ai: None, item: None, always_visible: false, level: 1}
+        Object{x, y, symbol, color, name: name.into(), blocks, alive: false, attackable: None, ai: None, item: None, always_visible: false, level: 1, count: 1, loot: vec![], shop: None, disposition: Disposition::default(), dialogue: None, speed: config::NORMAL_SPEED, energy: 0, boss: None, is_door: false, name_key: None}
     }
 
+    // an object whose name is shown in the player's language
+    pub fn translated(x: i32, y: i32, symbol: char, color: Color, name_key: NameKey, blocks: bool) -> Self {
+        let mut object = Object::new(x, y, symbol, color, &name_key.text(), blocks);
+        object.name_key = Some(name_key);
+        object
+    }
+
+
+    pub fn new_item(x: i32, y: i32, item: Item) -> Self {
+        let mut object = Object::new(x, y, item.symbol(), item.color(), &item.name(), false);
+        object.item = Some(item);
+        object.always_visible = true;
+        object
+    }
 
+    // translates the name again into the current language, after loading a save
+    pub fn refresh_name(&mut self) {
+        if let Some(name_key) = &self.name_key {
+            self.name = name_key.text();
+        } else if let Some(item) = self.item {
+            self.name = item.name();
+        }
+    }
 
     pub fn draw(&self, screen: &mut dyn Console) {
         screen.set_default_foreground(self.color);
@@ -179,13 +700,14 @@ impl Object {
     pub fn attack(&mut self, target: &mut Object, game: &mut Game) {
         let damage = self.attackable.map_or(0, |a| a.damage) - target.attackable.map_or(0, |a| a.armor);
         if damage > 0 {
-            game.messages.add(format!("{} dealt {} damage to {}", self.name, damage, target.name), WHITE);
+            game.messages.add_category(MessageCategory::Combat, tr!("combat.hit", attacker = self.name, damage = damage, target = target.name), WHITE);
+            game.animations.push(Animation { x: target.x, y: target.y, symbol: '*', color: RED, frames: config::HIT_ANIMATION_FRAMES });
             if let Some(xp) = target.get_damage(damage, game) {
                 self.attackable.as_mut().unwrap().xp += xp;
             }
         }
         else {
-            game.messages.add(format!("{}'s armor is stronger than {}'s damage", target.name, self.name), WHITE);
+            game.messages.add_category(MessageCategory::Combat, tr!("combat.blocked", target = target.name, attacker = self.name), WHITE);
         }
     }
 
@@ -212,11 +734,71 @@ impl Object {
 // map is 2-dimension list of tiles
 pub type Map = Vec<Vec<Tile>>;
 
+#[derive(Deserialize, Serialize)]
 pub struct Game{
     pub map: Map,
     pub messages: Messages,
     pub inventory: Vec<Object>,
     pub level: u32,
+    pub gold: i32,
+    // items dropped during a turn, placed on the map by the game loop
+    pub dropped_items: Vec<Object>,
+    // monsters called by bosses during a turn, placed on the map by the game loop
+    #[serde(skip)]
+    pub summoned: Vec<Object>,
+    // names of the bosses met in this run, each boss appears only once
+    #[serde(default)]
+    pub bosses: Vec<String>,
+    pub quests: Vec<Quest>,
+    // key bindings are kept in their own config file, not in the save
+    #[serde(skip, default = "input::load_bindings")]
+    pub bindings: input::Bindings,
+    // action repeated by the game loop every turn until it is finished or interrupted
+    #[serde(skip)]
+    pub auto_action: Option<AutoAction>,
+    pub mode: GameMode,
+    #[serde(skip)]
+    pub animations: Vec<Animation>,
+    pub stats: RunStats,
+}
+
+// totals shown on the game over screen
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct RunStats {
+    pub turns: i32,
+    pub kills: i32,
+}
+
+// in turn-based mode monsters act after the player, in real-time mode on a timer
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum GameMode {
+    TurnBased,
+    RealTime,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Animation {
+    pub x: i32,
+    pub y: i32,
+    pub symbol: char,
+    pub color: Color,
+    pub frames: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AutoAction {
+    Rest {
+        until_healed: bool,
+        hp: i32,
+        turns: i32,
+    },
+    Travel {
+        x: i32,
+        y: i32,
+    },
+    Explore {
+        hp: i32,
+    },
 }
 
 
@@ -227,7 +809,7 @@ pub enum PlayerAction {
     Exit,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Attackable {
     pub max_hp: i32,
     pub hp: i32,
@@ -237,16 +819,65 @@ pub struct Attackable {
     pub on_death: DeathCallback,
 }
 
-#[derive(Clone, Debug, PartialEq)] 
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub enum Ai {
     Basic,
     Blind {
         prev_ai: Box<Ai>, 
         num_turns: i32
     },
+    Wanderer,
+    Coward,
+    Guard,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+// the behaviour a monster config asks for:
+// basic - chases the player in view and attacks
+// wanderer - like basic, but roams around while the player is out of view
+// coward - like basic, but runs away when badly wounded
+// guard - never leaves its place, attacks only when the player is next to it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AiKind {
+    #[default]
+    Basic,
+    Wanderer,
+    Coward,
+    Guard,
+}
+
+impl AiKind {
+    pub const ALL: [AiKind; 4] = [AiKind::Basic, AiKind::Wanderer, AiKind::Coward, AiKind::Guard];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AiKind::Basic => "basic",
+            AiKind::Wanderer => "wanderer",
+            AiKind::Coward => "coward",
+            AiKind::Guard => "guard",
+        }
+    }
+
+    pub fn ai(self) -> Ai {
+        match self {
+            AiKind::Basic => Ai::Basic,
+            AiKind::Wanderer => Ai::Wanderer,
+            AiKind::Coward => Ai::Coward,
+            AiKind::Guard => Ai::Guard,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Disposition {
+    #[default]
+    Hostile,
+    Neutral,
+    Friendly,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
 pub enum DeathCallback {
     Player,
     Monster,
@@ -265,50 +896,200 @@ impl DeathCallback {
 
 
 fn player_death(player: &mut Object, game: &mut Game) {
-    game.messages.add("You died!", RED);
-    game.messages.add("Game Over!", RED);
+    game.messages.add_category(MessageCategory::Combat, tr!("combat.player_died"), RED);
+    game.messages.add_category(MessageCategory::Combat, tr!("combat.game_over"), RED);
 
     player.symbol = '%';
     player.color = DARK_RED;
 }
 
 fn monster_death(monster: &mut Object, game: &mut Game) {
-    game.messages.add(format!("{} is dead! You gain {} experience points", monster.name, monster.attackable.unwrap().xp), ORANGE);
+    game.messages.add_category(MessageCategory::Combat, tr!("combat.monster_died", name = monster.name, xp = monster.attackable.unwrap().xp), ORANGE);
+    game.stats.kills += 1;
+    if monster.boss.take().is_some() {
+        game.messages.add_category(MessageCategory::Level, tr!("boss.defeated", name = monster.name), LIGHT_MAGENTA);
+    }
+    quests::on_monster_killed(&monster.name, game);
+    drop_loot(monster, game);
     monster.symbol = '%';
     monster.color = DARK_RED;
     monster.blocks = false;
     monster.attackable = None;
     monster.ai = None;
-    monster.name = format!("remains of {}", monster.name);
+    monster.dialogue = None;
+    let name_key = NameKey { key: "object.remains".into(), name: Some(monster.name.clone()) };
+    monster.name = name_key.text();
+    monster.name_key = Some(name_key);
 }
 
+fn drop_loot(monster: &Object, game: &mut Game) {
+    for loot in &monster.loot {
+        let chance = loot.chance + loot.depth_bonus * (game.level as f32 - 1.0);
+        if myengine::random::<f32>() < chance {
+            let mut item = Object::new_item(monster.x, monster.y, loot.item);
+            item.count = myengine::gen_range(loot.min_count..loot.max_count + 1);
+            if item.count > 0 {
+                game.messages.add_category(MessageCategory::Loot, tr!("loot.dropped", name = monster.name, item = item.name, count = item.count), GOLD);
+                game.dropped_items.push(item);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum MessageCategory {
+    Combat,
+    Loot,
+    System,
+    Level,
+}
+
+impl MessageCategory {
+    pub const ALL: [MessageCategory; 4] = [MessageCategory::Combat, MessageCategory::Loot, MessageCategory::System, MessageCategory::Level];
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Message {
+    pub text: String,
+    pub color: Color,
+    pub category: MessageCategory,
+    // how many times in a row the message was repeated
+    pub count: i32,
+}
+
+impl Message {
+    pub fn display(&self) -> String {
+        if self.count > 1 {
+            format!("{} x{}", self.text, self.count)
+        } else {
+            self.text.clone()
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
 pub struct Messages {
-    messages: Vec<(String, Color)>,
+    messages: VecDeque<Message>,
 }
 
 impl Messages {
     pub fn new() -> Self {
-        Self { messages: vec![] }
+        Self { messages: VecDeque::new() }
     }
 
     pub fn add<T: Into<String>>(&mut self, message: T, color: Color) {
-        self.messages.push((message.into(), color));
+        self.add_category(MessageCategory::System, message, color);
+    }
+
+    pub fn add_category<T: Into<String>>(&mut self, category: MessageCategory, message: T, color: Color) {
+        let text = message.into();
+        if let Some(last) = self.messages.back_mut() {
+            if last.text == text && last.category == category {
+                last.count += 1;
+                last.color = color;
+                return;
+            }
+        }
+        self.messages.push_back(Message { text, color, category, count: 1 });
+        if self.messages.len() > config::MAX_MESSAGES_HISTORY {
+            self.messages.pop_front();
+        }
     }
 
-    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &(String, Color)> {
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Message> {
         self.messages.iter()
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Item {
     Heal,
     Fire,
     DoubleDamage,
     Blind,
+    Gold,
+}
+
+impl Item {
+    pub fn name(self) -> String {
+        use Item::*;
+        match self {
+            Heal => tr!("item.heal"),
+            Fire => tr!("item.fire"),
+            DoubleDamage => tr!("item.double_damage"),
+            Blind => tr!("item.blind"),
+            Gold => tr!("item.gold"),
+        }
+    }
+
+    pub fn symbol(self) -> char {
+        use Item::*;
+        match self {
+            Heal => '!',
+            Fire => '#',
+            DoubleDamage => '$',
+            Blind => '?',
+            Gold => '*',
+        }
+    }
+
+    pub fn color(self) -> Color {
+        use Item::*;
+        match self {
+            Heal => YELLOW,
+            Fire => RED,
+            DoubleDamage => LIGHT_BLUE,
+            Blind => GREY,
+            Gold => GOLD,
+        }
+    }
+
+    pub fn weight(self) -> i32 {
+        use Item::*;
+        match self {
+            Heal => config::HEAL_WEIGHT,
+            Fire => config::FIRE_SCROLL_WEIGHT,
+            DoubleDamage => config::DOUBLE_DAMAGE_WEIGHT,
+            Blind => config::BLIND_WEIGHT,
+            Gold => 0,
+        }
+    }
+
+    // an item missing from the item config is worth nothing
+    pub fn price(self) -> i32 {
+        resources::items().iter().find(|config| config.item == self).map_or(0, |config| config.price)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Shop {
+    pub stock: Vec<Item>,
 }
 
 pub enum UseResult {
     UsedUp,
     Cancelled,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn difficulty_scale_rounds_to_the_nearest_value() {
+        assert_eq!(DifficultyConfig::scale(10, 1.0), 10);
+        assert_eq!(DifficultyConfig::scale(10, 1.25), 13);
+        assert_eq!(DifficultyConfig::scale(3, 0.5), 2);
+        assert_eq!(DifficultyConfig::scale(7, 0.0), 0);
+    }
+
+    #[test]
+    fn difficulty_rows_apply_from_their_level_on() {
+        let mut table = DifficultyConfig::defaults();
+        table.extend(DifficultyConfig::defaults().into_iter().map(|row| DifficultyConfig { from_level: 4, ..row }));
+        assert_eq!(DifficultyConfig::for_level(&table, 1).unwrap().from_level, 1);
+        assert_eq!(DifficultyConfig::for_level(&table, 3).unwrap().from_level, 1);
+        assert_eq!(DifficultyConfig::for_level(&table, 9).unwrap().from_level, 4);
+    }
+}
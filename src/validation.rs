@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+
+use crate::structures;
+use crate::myengine;
+use crate::config as config; // change for other game
+
+
+fn error(entry: &str, message: String) -> structures::ValidationError {
+    structures::ValidationError { entry: entry.to_string(), message }
+}
+
+pub fn validate_monsters(monsters: &[structures::MonsterConfig]) -> Vec<structures::ValidationError> {
+    let mut errors = vec![];
+    let mut names: HashMap<&str, usize> = HashMap::new();
+
+    for (index, monster) in monsters.iter().enumerate() {
+        let entry = monster_entry(index, monster);
+
+        if monster.name.trim().is_empty() {
+            errors.push(error(&entry, "name is empty".into()));
+        } else if let Some(first) = names.insert(monster.name.as_str(), index) {
+            errors.push(error(&entry, format!("name is already used by monster {}", first + 1)));
+        }
+        // the font only has printable ascii glyphs
+        if !monster.symbol.is_ascii_graphic() {
+            errors.push(error(&entry, format!("symbol '{}' can not be drawn, use a printable ascii character", monster.symbol.escape_default())));
+        }
+        if monster.max_hp <= 0 {
+            errors.push(error(&entry, format!("max HP must be positive, got {}", monster.max_hp)));
+        }
+        if monster.damage <= 0 {
+            errors.push(error(&entry, format!("damage must be positive, got {}", monster.damage)));
+        }
+        if monster.armor < 0 {
+            errors.push(error(&entry, format!("armor can not be negative, got {}", monster.armor)));
+        }
+        if monster.xp < 0 {
+            errors.push(error(&entry, format!("xp can not be negative, got {}", monster.xp)));
+        }
+        if monster.speed <= 0 {
+            errors.push(error(&entry, format!("speed must be positive, got {}", monster.speed)));
+        }
+        if monster.min_depth < 1 {
+            errors.push(error(&entry, "min depth must be at least 1".into()));
+        }
+        if let Some(max_depth) = monster.max_depth.filter(|&max_depth| max_depth < monster.min_depth) {
+            errors.push(error(&entry, format!("max depth {} is below min depth {}", max_depth, monster.min_depth)));
+        }
+        if monster.min_group < 1 || monster.min_group > monster.max_group {
+            errors.push(error(&entry, format!("group size range {}..{} is invalid", monster.min_group, monster.max_group)));
+        }
+        if let Some(boss) = &monster.boss {
+            validate_boss(&entry, boss, &mut errors);
+        }
+        for (loot_index, loot) in monster.loot.iter().enumerate() {
+            let loot_entry = format!("{} loot {}", entry, loot_index + 1);
+            if !(0.0..=1.0).contains(&loot.chance) {
+                errors.push(error(&loot_entry, format!("chance must be between 0 and 1, got {}", loot.chance)));
+            }
+            if loot.depth_bonus < 0.0 {
+                errors.push(error(&loot_entry, format!("depth bonus can not be negative, got {}", loot.depth_bonus)));
+            }
+            if loot.min_count < 0 || loot.min_count > loot.max_count {
+                errors.push(error(&loot_entry, format!("count range {}..{} is invalid", loot.min_count, loot.max_count)));
+            }
+        }
+    }
+    errors
+}
+
+fn monster_entry(index: usize, monster: &structures::MonsterConfig) -> String {
+    format!("monster {} '{}'", index + 1, monster.name)
+}
+
+// whether none of the errors is about the monster at `index`, its loot or its boss section
+pub fn is_valid_monster(index: usize, monster: &structures::MonsterConfig, errors: &[structures::ValidationError]) -> bool {
+    let entry = monster_entry(index, monster);
+    !errors.iter().any(|error| error.entry == entry || error.entry.starts_with(&format!("{} ", entry)))
+}
+
+fn validate_boss(entry: &str, boss: &structures::BossConfig, errors: &mut Vec<structures::ValidationError>) {
+    if boss.depth < 1 {
+        errors.push(error(entry, "boss depth must be at least 1".into()));
+    }
+    for ability in &boss.abilities {
+        match ability {
+            structures::BossAbility::Regenerate { amount } if *amount <= 0 => {
+                errors.push(error(entry, format!("regenerate amount must be positive, got {}", amount)));
+            }
+            structures::BossAbility::Summon { every_turns: 0, .. } => {
+                errors.push(error(entry, "summon every_turns must be positive".into()));
+            }
+            structures::BossAbility::Enrage { below_hp_fraction, .. } if !(0.0..=1.0).contains(below_hp_fraction) => {
+                errors.push(error(entry, format!("enrage fraction must be between 0 and 1, got {}", below_hp_fraction)));
+            }
+            _ => {}
+        }
+    }
+    for loot in &boss.guaranteed_loot {
+        if loot.count <= 0 {
+            errors.push(error(entry, format!("guaranteed loot count must be positive, got {}", loot.count)));
+        }
+    }
+}
+
+pub fn validate_map(map: &structures::MapConfig) -> Vec<structures::ValidationError> {
+    let mut errors = vec![];
+    let entry = "map colors";
+    let light_wall = (map.light_wall_r, map.light_wall_g, map.light_wall_b);
+    let dark_wall = (map.dark_wall_r, map.dark_wall_g, map.dark_wall_b);
+    let light_ground = (map.light_ground_r, map.light_ground_g, map.light_ground_b);
+    let dark_ground = (map.dark_ground_r, map.dark_ground_g, map.dark_ground_b);
+
+    // walls are only told apart from the ground by their color
+    if light_wall == light_ground {
+        errors.push(error(entry, "lit walls and lit ground have the same color".into()));
+    }
+    if dark_wall == dark_ground {
+        errors.push(error(entry, "explored walls and explored ground have the same color".into()));
+    }
+    errors
+}
+
+pub fn validate_dialogues(dialogues: &[structures::DialogueConfig]) -> Vec<structures::ValidationError> {
+    let mut errors = vec![];
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+
+    for (index, dialogue) in dialogues.iter().enumerate() {
+        let entry = format!("dialogue {} '{}'", index + 1, dialogue.id);
+        if let Some(first) = ids.insert(dialogue.id.as_str(), index) {
+            errors.push(error(&entry, format!("id is already used by dialogue {}", first + 1)));
+        }
+        if dialogue.nodes.is_empty() {
+            errors.push(error(&entry, "has no nodes".into()));
+        }
+        for (node_index, node) in dialogue.nodes.iter().enumerate() {
+            let node_entry = format!("{} node {}", entry, node_index);
+            if node.options.is_empty() {
+                errors.push(error(&node_entry, "has no options".into()));
+            }
+            // options are chosen with the letters a-z
+            if node.options.len() > 26 {
+                errors.push(error(&node_entry, format!("has {} options, at most 26 fit in the menu", node.options.len())));
+            }
+            for option in &node.options {
+                if let Some(next) = option.next.filter(|&next| next >= dialogue.nodes.len()) {
+                    errors.push(error(&node_entry, format!("option '{}' leads to node {} which does not exist", option.text, next)));
+                }
+            }
+        }
+    }
+    errors
+}
+
+pub fn validate_quests(quests: &[structures::QuestConfig]) -> Vec<structures::ValidationError> {
+    let mut errors = vec![];
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+
+    for (index, quest) in quests.iter().enumerate() {
+        let entry = format!("quest {} '{}'", index + 1, quest.id);
+        if let Some(first) = ids.insert(quest.id.as_str(), index) {
+            errors.push(error(&entry, format!("id is already used by quest {}", first + 1)));
+        }
+        if quest.name.trim().is_empty() {
+            errors.push(error(&entry, "name is empty".into()));
+        }
+        if quest.objective.target() <= 0 {
+            errors.push(error(&entry, format!("objective target must be positive, got {}", quest.objective.target())));
+        }
+        if quest.reward.xp < 0 || quest.reward.gold < 0 {
+            errors.push(error(&entry, "reward can not be negative".into()));
+        }
+    }
+    errors
+}
+
+pub fn validate_difficulty(difficulty: &[structures::DifficultyConfig]) -> Vec<structures::ValidationError> {
+    let mut errors = vec![];
+    match difficulty.first() {
+        None => errors.push(error("difficulty", "the table has no rows".into())),
+        Some(first) if first.from_level != 1 => errors.push(error("difficulty", format!("the first row must start at level 1, got {}", first.from_level))),
+        _ => {}
+    }
+    for (index, row) in difficulty.iter().enumerate() {
+        let entry = format!("difficulty row {} (level {})", index + 1, row.from_level);
+        if index > 0 && row.from_level <= difficulty[index - 1].from_level {
+            errors.push(error(&entry, "rows must be sorted by level, each level once".into()));
+        }
+        if row.max_room_monsters < 0 || row.max_room_items < 0 {
+            errors.push(error(&entry, "monsters and items per room can not be negative".into()));
+        }
+        let multipliers = [("hp", row.hp_multiplier), ("damage", row.damage_multiplier), ("armor", row.armor_multiplier), ("xp", row.xp_multiplier)];
+        for (name, multiplier) in multipliers.iter().filter(|(_, multiplier)| *multiplier < 0.0) {
+            errors.push(error(&entry, format!("{} multiplier can not be negative, got {}", name, multiplier)));
+        }
+    }
+    errors
+}
+
+pub fn validate_items(items: &[structures::ItemConfig]) -> Vec<structures::ValidationError> {
+    let mut errors = vec![];
+    for (index, config) in items.iter().enumerate() {
+        let entry = format!("item {} '{:?}'", index + 1, config.item);
+        if items[..index].iter().any(|other| other.item == config.item) {
+            errors.push(error(&entry, "item is listed more than once".into()));
+        }
+        if config.price < 0 {
+            errors.push(error(&entry, format!("price can not be negative, got {}", config.price)));
+        }
+    }
+    errors
+}
+
+// checks the references between the config files, which each file alone can not know about
+pub fn validate_references(
+    monsters: &[structures::MonsterConfig],
+    dialogues: &[structures::DialogueConfig],
+    quests: &[structures::QuestConfig],
+    difficulty: &[structures::DifficultyConfig],
+) -> Vec<structures::ValidationError> {
+    let mut errors = vec![];
+    let has_dialogue = |id: &str| dialogues.iter().any(|dialogue| dialogue.id == id);
+
+    for (index, monster) in monsters.iter().enumerate() {
+        let entry = format!("monster {} '{}'", index + 1, monster.name);
+        if let Some(dialogue) = monster.dialogue.as_deref().filter(|&id| !has_dialogue(id)) {
+            errors.push(error(&entry, format!("dialogue '{}' does not exist", dialogue)));
+        }
+        for ability in monster.boss.iter().flat_map(|boss| &boss.abilities) {
+            if let structures::BossAbility::Summon { monster: summoned, .. } = ability {
+                if !monsters.iter().any(|config| &config.name == summoned) {
+                    errors.push(error(&entry, format!("summons monster '{}' which does not exist", summoned)));
+                }
+            }
+        }
+    }
+    for dialogue in dialogues {
+        for node in &dialogue.nodes {
+            for option in &node.options {
+                if let Some(structures::DialogueAction::GiveQuest(quest_id)) = &option.action {
+                    if !quests.iter().any(|quest| &quest.id == quest_id) {
+                        errors.push(error(&format!("dialogue '{}'", dialogue.id), format!("gives quest '{}' which does not exist", quest_id)));
+                    }
+                }
+            }
+        }
+    }
+    for row in difficulty {
+        for name in row.spawn_weights.keys().filter(|&name| !monsters.iter().any(|monster| &monster.name == name)) {
+            errors.push(error(&format!("difficulty level {}", row.from_level), format!("spawn weight is set for monster '{}' which does not exist", name)));
+        }
+    }
+    for quest in quests {
+        let entry = format!("quest '{}'", quest.id);
+        match &quest.objective {
+            structures::Objective::Kill { monster, .. } if !monsters.iter().any(|config| &config.name == monster) => {
+                errors.push(error(&entry, format!("monster '{}' does not exist", monster)));
+            }
+            structures::Objective::TalkTo { npc } if !has_dialogue(npc) => {
+                errors.push(error(&entry, format!("dialogue '{}' does not exist", npc)));
+            }
+            _ => {}
+        }
+    }
+    errors
+}
+
+// validates every config file, for the validate-config command
+pub fn validate_all() -> Vec<(String, Vec<String>)> {
+    let mut report = vec![];
+    let mut add = |name: String, errors: Vec<String>| report.push((name, errors));
+    // configs are reported under the file that was read, whatever its format
+    let file = |file_name: &str| myengine::config_file_path(file_name).file_name().map_or_else(|| file_name.to_string(), |name| name.to_string_lossy().into_owned());
+
+    let monsters = myengine::read_monster_config();
+    let map = myengine::read_map_config();
+    let dialogues = myengine::read_dialogue_config();
+    let quests = myengine::read_quest_config();
+    let difficulty = myengine::read_difficulty_config();
+    let items = myengine::read_item_config();
+
+    add(file(config::CONFIG_MONSTER_FILE_NAME), problems(&monsters));
+    add(file(config::CONFIG_MAP_FILE_NAME), problems(&map));
+    add(file(config::CONFIG_DIALOGUE_FILE_NAME), problems(&dialogues));
+    add(file(config::CONFIG_QUEST_FILE_NAME), problems(&quests));
+    add(file(config::CONFIG_DIFFICULTY_FILE_NAME), problems(&difficulty));
+    add(file(config::CONFIG_ITEM_FILE_NAME), problems(&items));
+
+    if let (Ok(monsters), Ok(dialogues), Ok(quests), Ok(difficulty)) = (&monsters, &dialogues, &quests, &difficulty) {
+        let errors = validate_references(monsters, dialogues, quests, difficulty);
+        add("references".to_string(), errors.iter().map(ToString::to_string).collect());
+    }
+    report
+}
+
+fn problems<T>(result: &Result<T, structures::ConfigError>) -> Vec<String> {
+    match result {
+        Ok(_) => vec![],
+        Err(structures::ConfigError::Invalid { errors, .. }) => errors.iter().map(ToString::to_string).collect(),
+        Err(error) => vec![error.to_string()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(errors: &[structures::ValidationError]) -> Vec<String> {
+        errors.iter().map(|error| error.message.clone()).collect()
+    }
+
+    #[test]
+    fn default_monsters_are_valid() {
+        assert!(validate_monsters(&structures::MonsterConfig::defaults()).is_empty());
+    }
+
+    #[test]
+    fn invalid_loot_ranges_are_reported() {
+        let mut monsters = structures::MonsterConfig::defaults();
+        monsters[0].loot = vec![
+            structures::LootConfig { item: structures::Item::Heal, chance: 1.5, min_count: 1, max_count: 1, depth_bonus: 0.0 },
+            structures::LootConfig { item: structures::Item::Gold, chance: 0.5, min_count: 3, max_count: 1, depth_bonus: -0.1 },
+        ];
+        let errors = validate_monsters(&monsters);
+        assert_eq!(messages(&errors), vec![
+            "chance must be between 0 and 1, got 1.5".to_string(),
+            "depth bonus can not be negative, got -0.1".to_string(),
+            "count range 3..1 is invalid".to_string(),
+        ]);
+        assert_eq!(errors[0].entry, "monster 1 'Orc' loot 1");
+        assert_eq!(errors[2].entry, "monster 1 'Orc' loot 2");
+    }
+
+    #[test]
+    fn invalid_group_ranges_are_reported() {
+        let mut monsters = structures::MonsterConfig::defaults();
+        monsters[0].min_group = 0;
+        monsters[1].min_group = 4;
+        monsters[1].max_group = 2;
+        assert_eq!(messages(&validate_monsters(&monsters)), vec![
+            "group size range 0..1 is invalid".to_string(),
+            "group size range 4..2 is invalid".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn unknown_summon_targets_are_reported() {
+        let mut monsters = structures::MonsterConfig::defaults();
+        monsters[1].boss = Some(structures::BossConfig {
+            depth: 2,
+            abilities: vec![
+                structures::BossAbility::Summon { monster: "Orc".into(), every_turns: 3, max: 2 },
+                structures::BossAbility::Summon { monster: "Goblin".into(), every_turns: 3, max: 2 },
+            ],
+            guaranteed_loot: vec![],
+            announcement: String::new(),
+        });
+        let errors = validate_references(&monsters, &[], &[], &structures::DifficultyConfig::defaults());
+        assert_eq!(messages(&errors), vec!["summons monster 'Goblin' which does not exist".to_string()]);
+        assert_eq!(errors[0].entry, "monster 2 'Troll'");
+    }
+
+    #[test]
+    fn duplicate_items_and_negative_prices_are_reported() {
+        let mut items = structures::ItemConfig::defaults();
+        assert!(validate_items(&items).is_empty());
+        items[1].price = -5;
+        items.push(structures::ItemConfig { item: structures::Item::Heal, price: 10, in_shop: false });
+        let errors = validate_items(&items);
+        assert_eq!(messages(&errors), vec![
+            "price can not be negative, got -5".to_string(),
+            "item is listed more than once".to_string(),
+        ]);
+        assert_eq!(errors[1].entry, "item 6 'Heal'");
+    }
+
+    #[test]
+    fn only_the_monsters_with_errors_are_invalid() {
+        let mut monsters = structures::MonsterConfig::defaults();
+        monsters[0].loot = vec![structures::LootConfig { item: structures::Item::Heal, chance: 2.0, min_count: 1, max_count: 1, depth_bonus: 0.0 }];
+        let errors = validate_monsters(&monsters);
+        assert!(!is_valid_monster(0, &monsters[0], &errors));
+        assert!(is_valid_monster(1, &monsters[1], &errors));
+    }
+}